@@ -1,11 +1,7 @@
-use hacam_lib_rs::{
-    cam::HaCam,
-    settings::{LiveViewResolution},
-};
+use hacam_lib_rs::prelude::*;
 use image::{codecs::{png::PngEncoder}, ExtendedColorType, ImageEncoder};
-use openh264::formats::YUVSource as _;
+use hacam_lib_rs::decode::new_decoder;
 use tokio::time::sleep;
-use yuv::{yuv420_to_rgb, YuvPlanarImage, YuvRange, YuvStandardMatrix};
 use std::{fs::File, time::Duration};
 
 #[tokio::main]
@@ -30,45 +26,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     );
 
-    let mut decoder = openh264::decoder::Decoder::new()?;
+    let mut decoder = new_decoder()?;
 
     let mut frame_cnt = 0;
 
     while let Ok((_, frame)) = cam.get_live_view_frame().await {
-        for packet in openh264::nal_units(&frame.data) {
-            let decoded_pkt = decoder.decode(packet);
-
-            if let Ok(Some(decoded_yuv_frame)) = decoded_pkt {
-                let (w, h) = decoded_yuv_frame.dimensions();
-                let (y, u, v) = decoded_yuv_frame.strides();
-
-                let yuv_image = YuvPlanarImage {
-                    y_plane: decoded_yuv_frame.y(),
-                    u_plane: decoded_yuv_frame.u(),
-                    v_plane: decoded_yuv_frame.v(),
-                    y_stride: y as u32,
-                    u_stride: u as u32,
-                    v_stride: v as u32,
-                    width: w as u32,
-                    height: h as u32,
-                };
-
-                let column_cnt = 3 * w; // this is the stride - 3 represents the amount of color channels
-
-                let mut rgb_image = vec![0; column_cnt * h]; 
-
-                yuv420_to_rgb(
-                    &yuv_image,
-                    &mut rgb_image,
-                    column_cnt as u32,
-                    YuvRange::Full,
-                    YuvStandardMatrix::Bt601,
-                )?;
-
-                let mut out_file = File::create(format!("frame-{frame_cnt}.png"))?;
-                let png = PngEncoder::new(&mut out_file);
-                png.write_image(&rgb_image, w as u32, h as u32, ExtendedColorType::Rgb8)?;
-            }
+        if let Some(rgb_frame) = frame.decode_rgb(&mut decoder)? {
+            let mut out_file = File::create(format!("frame-{frame_cnt}.png"))?;
+            let png = PngEncoder::new(&mut out_file);
+            png.write_image(
+                &rgb_frame.data,
+                rgb_frame.width,
+                rgb_frame.height,
+                ExtendedColorType::Rgb8,
+            )?;
         }
 
         frame_cnt += 1;
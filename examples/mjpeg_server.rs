@@ -0,0 +1,45 @@
+use futures::StreamExt as _;
+use hacam_lib_rs::{mjpeg, prelude::*};
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::TcpListener,
+};
+
+#[tokio::main]
+/// This example serves the live view as an MJPEG stream, which can be viewed in a browser at http://127.0.0.1:8080.
+/// Only one viewer is served at a time.
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cam = HaCam::new()?;
+
+    cam.initialize_comm().await?;
+    cam.start_live_view(LiveViewResolution::Low).await?;
+
+    let listener = TcpListener::bind("127.0.0.1:8080").await?;
+
+    println!("Serving the live view at http://127.0.0.1:8080");
+
+    loop {
+        let (mut socket, addr) = listener.accept().await?;
+
+        println!("Serving {addr}");
+
+        // The request itself doesn't matter, every request gets the stream.
+        let mut request = [0; 1024];
+        let _ = socket.read(&mut request).await?;
+
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+            mjpeg::CONTENT_TYPE
+        );
+        socket.write_all(header.as_bytes()).await?;
+
+        let mut frames = Box::pin(cam.jpeg_stream(15.0, 80)?);
+
+        while let Some(jpeg) = frames.next().await {
+            if socket.write_all(&mjpeg::part(&jpeg?)).await.is_err() {
+                println!("{addr} disconnected");
+                break;
+            }
+        }
+    }
+}
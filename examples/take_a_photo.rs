@@ -1,6 +1,6 @@
 use std::{fs::File, io::Write};
 
-use hacam_lib_rs::{cam::HaCam, settings::PictureOrientation, util::CamUtil};
+use hacam_lib_rs::prelude::*;
 
 #[tokio::main]
 /// This example takes a photo and saves it in a file.
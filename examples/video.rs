@@ -1,11 +1,8 @@
 use std::fs::File;
-use hacam_lib_rs::{
-    cam::HaCam,
-    settings::{Resolution as _, SettingType, VideoResolution},
-};
+use hacam_lib_rs::{mux::Mp4Recorder, prelude::*, settings::SettingType};
 
 #[tokio::main]
-/// This example takes a 5-second video and saves it as a MP4 file (using the minimp4 crate)
+/// This example takes a 5-second video and saves it as a MP4 file (requires the `mux` feature)
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut cam = HaCam::new()?;
 
@@ -23,14 +20,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         if cam.check_start_recording_request().await? { "" } else { "un" }
     );
 
-    let mut mp4muxer = minimp4::Mp4Muxer::new(File::create("video.mp4")?);
-
-    mp4muxer.init_video(res.w() as i32, res.h() as i32, false, "video");
+    let mut recorder = Mp4Recorder::new(File::create("video.mp4")?, res);
 
     let mut frame_cnt = 0;
 
     while let Ok((_, frame)) = cam.get_live_view_frame().await {
-        mp4muxer.write_video_with_fps(&frame.data, 30);
+        recorder.push_frame(&frame);
 
         frame_cnt += 1;
 
@@ -46,7 +41,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         if cam.check_stop_recording_request().await? { "" } else { "un" }
     );
 
-    mp4muxer.close();
+    recorder.finish();
 
     Ok(())
 }
@@ -0,0 +1,370 @@
+use tokio::runtime::Runtime;
+
+use crate::{
+    cam::{
+        CaptureStatus, HaCam, HaCamConfig, LiveViewFrame, StatusSnapshot, ThermalStatus, Versions,
+    },
+    settings::*,
+    util::{CamUtil as _, CapturedPicture},
+    CamResult,
+};
+
+/// Blocking wrapper around the `HaCam` struct, for consumers which don't use an async runtime.
+///
+/// The wrapper owns a current-thread Tokio runtime and runs every command to completion
+/// via `Runtime::block_on`. Only the commonly used functions are mirrored,
+/// the rest can be accessed through `inner_mut` and `block_on`.
+///
+/// A single `HaCamBlocking` must not be shared across threads - the commands are not meant
+/// to be interleaved, so give each thread its own camera handle (or move it between threads).
+/// It also must not be used from within an async context, as `block_on` panics there.
+pub struct HaCamBlocking {
+    cam: HaCam,
+    rt: Runtime,
+}
+
+impl HaCamBlocking {
+    /// Opens the USB connection to the camera with default parameters.
+    ///
+    /// The caller should then use the `initialize_comm` function,
+    /// which initializes the data communication to the camera.
+    pub fn new() -> CamResult<Self> {
+        Self::from_cam(HaCam::new()?)
+    }
+
+    /// Opens the USB connection to the camera with custom parameters (see `HaCam::with_config`).
+    pub fn with_config(config: HaCamConfig) -> CamResult<Self> {
+        Self::from_cam(HaCam::with_config(config)?)
+    }
+
+    /// Waits for the camera to appear and opens it (see `HaCam::scan_and_open`).
+    pub fn scan_and_open(
+        timeout: std::time::Duration,
+        poll: std::time::Duration,
+    ) -> CamResult<Self> {
+        let rt = Self::build_runtime()?;
+        let cam = rt.block_on(HaCam::scan_and_open(timeout, poll))?;
+
+        Ok(Self { cam, rt })
+    }
+
+    /// Wraps an already opened camera.
+    pub fn from_cam(cam: HaCam) -> CamResult<Self> {
+        let rt = Self::build_runtime()?;
+
+        Ok(Self { cam, rt })
+    }
+
+    fn build_runtime() -> CamResult<Runtime> {
+        Ok(tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?)
+    }
+
+    /// Returns a reference to the wrapped camera.
+    pub fn inner(&self) -> &HaCam {
+        &self.cam
+    }
+
+    /// Returns a mutable reference to the wrapped camera.
+    pub fn inner_mut(&mut self) -> &mut HaCam {
+        &mut self.cam
+    }
+
+    /// Unwraps the camera, dropping the runtime.
+    pub fn into_inner(self) -> HaCam {
+        self.cam
+    }
+
+    /// Runs the provided future to completion on the wrapper's runtime.
+    /// Useful for calling functions which aren't mirrored by the wrapper.
+    pub fn block_on<F: std::future::Future>(&self, fut: F) -> F::Output {
+        self.rt.block_on(fut)
+    }
+
+    /// See `HaCam::initialize_comm`.
+    pub fn initialize_comm(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.initialize_comm())
+    }
+
+    /// See `HaCam::app_connect`.
+    pub fn app_connect(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.app_connect())
+    }
+
+    /// See `HaCam::ensure_connected`.
+    pub fn ensure_connected(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.ensure_connected())
+    }
+
+    /// See `HaCam::wake`.
+    pub fn wake(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.wake())
+    }
+
+    /// See `HaCam::send_keepalive`.
+    pub fn send_keepalive(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.send_keepalive())
+    }
+
+    /// See `HaCam::ping`.
+    pub fn ping(&mut self) -> CamResult<bool> {
+        self.rt.block_on(self.cam.ping())
+    }
+
+    /// See `HaCam::reconnect`.
+    pub fn reconnect(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.reconnect())
+    }
+
+    /// See `HaCam::reset_usb`.
+    pub fn reset_usb(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.reset_usb())
+    }
+
+    /// See `HaCam::power_off`.
+    pub fn power_off(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.power_off())
+    }
+
+    /// See `HaCam::query_remaining_pic_num`.
+    pub fn query_remaining_pic_num(&mut self) -> CamResult<u8> {
+        self.rt.block_on(self.cam.query_remaining_pic_num())
+    }
+
+    /// See `HaCam::clear_camera_pic_buf`.
+    pub fn clear_camera_pic_buf(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.clear_camera_pic_buf())
+    }
+
+    /// See `HaCam::start_live_view`.
+    pub fn start_live_view(&mut self, resolution: LiveViewResolution) -> CamResult<()> {
+        self.rt.block_on(self.cam.start_live_view(resolution))
+    }
+
+    /// See `HaCam::start_live_view_confirmed`.
+    pub fn start_live_view_confirmed(
+        &mut self,
+        resolution: LiveViewResolution,
+        timeout: std::time::Duration,
+    ) -> CamResult<()> {
+        self.rt
+            .block_on(self.cam.start_live_view_confirmed(resolution, timeout))
+    }
+
+    /// See `HaCam::stop_live_view_confirmed`.
+    pub fn stop_live_view_confirmed(&mut self, timeout: std::time::Duration) -> CamResult<()> {
+        self.rt.block_on(self.cam.stop_live_view_confirmed(timeout))
+    }
+
+    /// See `HaCam::stop_live_view`.
+    pub fn stop_live_view(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.stop_live_view())
+    }
+
+    /// See `HaCam::is_live_view_active`.
+    pub fn is_live_view_active(&self) -> bool {
+        self.cam.is_live_view_active()
+    }
+
+    /// See `HaCam::active_live_view_resolution`.
+    pub fn active_live_view_resolution(&self) -> Option<LiveViewResolution> {
+        self.cam.active_live_view_resolution()
+    }
+
+    /// See `HaCam::check_live_view_status`.
+    pub fn check_live_view_status(&mut self) -> CamResult<bool> {
+        self.rt.block_on(self.cam.check_live_view_status())
+    }
+
+    /// See `HaCam::check_live_view_stop_request_status`.
+    pub fn check_live_view_stop_request_status(&mut self) -> CamResult<bool> {
+        self.rt.block_on(self.cam.check_live_view_stop_request_status())
+    }
+
+    /// See `HaCam::get_live_view_frame`.
+    pub fn get_live_view_frame(&mut self) -> CamResult<(ThermalStatus, LiveViewFrame)> {
+        self.rt.block_on(self.cam.get_live_view_frame())
+    }
+
+    /// See `HaCam::get_live_view_frame_with_timeout`.
+    pub fn get_live_view_frame_with_timeout(
+        &mut self,
+        frame_timeout: std::time::Duration,
+    ) -> CamResult<(ThermalStatus, LiveViewFrame)> {
+        self.rt
+            .block_on(self.cam.get_live_view_frame_with_timeout(frame_timeout))
+    }
+
+    /// See `HaCam::take_picture`.
+    pub fn take_picture(&mut self, orientation: PictureOrientation) -> CamResult<()> {
+        self.rt.block_on(self.cam.take_picture(orientation))
+    }
+
+    /// See `HaCam::check_capture_status`.
+    pub fn check_capture_status(&mut self) -> CamResult<CaptureStatus> {
+        self.rt.block_on(self.cam.check_capture_status())
+    }
+
+    /// See `HaCam::wait_for_capture`.
+    pub fn wait_for_capture(
+        &mut self,
+        poll: std::time::Duration,
+        timeout: std::time::Duration,
+    ) -> CamResult<CaptureStatus> {
+        self.rt.block_on(self.cam.wait_for_capture(poll, timeout))
+    }
+
+    /// See `HaCam::await_thumbnail`.
+    pub fn await_thumbnail(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> CamResult<(Vec<u8>, CaptureStatus)> {
+        self.rt.block_on(self.cam.await_thumbnail(timeout))
+    }
+
+    /// See `HaCam::get_thumbnail`.
+    pub fn get_thumbnail(&mut self) -> CamResult<Vec<u8>> {
+        self.rt.block_on(self.cam.get_thumbnail())
+    }
+
+    /// See `HaCam::get_partial_picture_buffer`.
+    pub fn get_partial_picture_buffer(
+        &mut self,
+        received_pic_data_len: u32,
+    ) -> CamResult<(Vec<u8>, bool)> {
+        self.rt
+            .block_on(self.cam.get_partial_picture_buffer(received_pic_data_len))
+    }
+
+    /// See `HaCam::verify_picture_transfer`.
+    pub fn verify_picture_transfer(&mut self) -> CamResult<bool> {
+        self.rt.block_on(self.cam.verify_picture_transfer())
+    }
+
+    /// See `HaCam::drain_pictures`.
+    pub fn drain_pictures(&mut self, on_picture: impl FnMut(Vec<u8>)) -> CamResult<usize> {
+        self.rt.block_on(self.cam.drain_pictures(on_picture))
+    }
+
+    /// See `CamUtil::take_picture_and_get`.
+    pub fn take_picture_and_get(
+        &mut self,
+        orientation: PictureOrientation,
+        on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
+        was_live_view_initialized: bool,
+    ) -> CamResult<Vec<u8>> {
+        self.rt.block_on(self.cam.take_picture_and_get(
+            orientation,
+            on_thumbnail,
+            was_live_view_initialized,
+        ))
+    }
+
+    /// See `CamUtil::take_picture_full`.
+    pub fn take_picture_full(
+        &mut self,
+        orientation: PictureOrientation,
+        was_live_view_initialized: bool,
+    ) -> CamResult<CapturedPicture> {
+        self.rt
+            .block_on(self.cam.take_picture_full(orientation, was_live_view_initialized))
+    }
+
+    /// See `CamUtil::take_picture_at`.
+    pub fn take_picture_at(
+        &mut self,
+        orientation: PictureOrientation,
+        resolution: PhotoResolution,
+        restore: bool,
+    ) -> CamResult<Vec<u8>> {
+        self.rt
+            .block_on(self.cam.take_picture_at(orientation, resolution, restore))
+    }
+
+    /// See `CamUtil::capture_to_path`.
+    pub fn capture_to_path(
+        &mut self,
+        orientation: PictureOrientation,
+        image_path: impl AsRef<std::path::Path> + Send,
+        thumbnail_path: Option<impl AsRef<std::path::Path> + Send>,
+    ) -> CamResult<(usize, Option<usize>)> {
+        self.rt
+            .block_on(self.cam.capture_to_path(orientation, image_path, thumbnail_path))
+    }
+
+    /// See `HaCam::is_recording`.
+    pub fn is_recording(&self) -> bool {
+        self.cam.is_recording()
+    }
+
+    /// See `HaCam::start_recording`.
+    pub fn start_recording(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.start_recording())
+    }
+
+    /// See `HaCam::stop_recording`.
+    pub fn stop_recording(&mut self) -> CamResult<()> {
+        self.rt.block_on(self.cam.stop_recording())
+    }
+
+    /// See `HaCam::check_start_recording_request`.
+    pub fn check_start_recording_request(&mut self) -> CamResult<bool> {
+        self.rt.block_on(self.cam.check_start_recording_request())
+    }
+
+    /// See `HaCam::check_stop_recording_request`.
+    pub fn check_stop_recording_request(&mut self) -> CamResult<bool> {
+        self.rt.block_on(self.cam.check_stop_recording_request())
+    }
+
+    /// See `HaCam::get_camera_status`.
+    pub fn get_camera_status(&mut self) -> CamResult<(u8, ThermalStatus)> {
+        self.rt.block_on(self.cam.get_camera_status())
+    }
+
+    /// See `HaCam::status_snapshot`.
+    pub fn status_snapshot(&mut self) -> CamResult<StatusSnapshot> {
+        self.rt.block_on(self.cam.status_snapshot())
+    }
+
+    /// See `HaCam::get_camera_info`.
+    pub fn get_camera_info(&mut self) -> CamResult<Option<String>> {
+        self.rt.block_on(self.cam.get_camera_info())
+    }
+
+    /// See `HaCam::get_scsi_version`.
+    pub fn get_scsi_version(&mut self) -> CamResult<Option<String>> {
+        self.rt.block_on(self.cam.get_scsi_version())
+    }
+
+    /// See `HaCam::get_versions`.
+    pub fn get_versions(&mut self) -> CamResult<Versions> {
+        self.rt.block_on(self.cam.get_versions())
+    }
+
+    /// See `HaCam::write_setting`.
+    pub fn write_setting(&mut self, setting: SettingType, value: u8) -> CamResult<()> {
+        self.rt.block_on(self.cam.write_setting(setting, value))
+    }
+
+    /// See `HaCam::read_setting`.
+    pub fn read_setting(&mut self, setting: SettingType) -> CamResult<u8> {
+        self.rt.block_on(self.cam.read_setting(setting))
+    }
+
+    /// See `HaCam::read_setting_raw`.
+    pub fn read_setting_raw(&mut self, setting: SettingType) -> CamResult<Vec<u8>> {
+        self.rt.block_on(self.cam.read_setting_raw(setting))
+    }
+
+    /// See `HaCam::read_all_settings`.
+    pub fn read_all_settings(&mut self) -> CamResult<CamSettings> {
+        self.rt.block_on(self.cam.read_all_settings())
+    }
+
+    /// See `HaCam::write_all_settings`.
+    pub fn write_all_settings(&mut self, settings: CamSettings) -> CamResult<()> {
+        self.rt.block_on(self.cam.write_all_settings(settings))
+    }
+}
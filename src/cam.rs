@@ -1,22 +1,167 @@
 use std::{io::Write as _};
-use nusb::transfer::{ControlOut, ControlType, Recipient};
+use nusb::transfer::{ControlOut, ControlType, Direction, EndpointType, Recipient};
 use log::*;
 use rand::Rng as _;
+use futures::Stream;
 
-use crate::{consts::{self, DEFAULT_PID, DEFAULT_VID, ENDPOINT_IN_ADDR, ENDPOINT_OUT_ADDR, RX_HEADER_MAGIC}, settings::*, CamError, CamResult};
+use crate::{consts::{self, DEFAULT_PID, DEFAULT_VID, ENDPOINT_IN_ADDR, ENDPOINT_OUT_ADDR, RX_HEADER_MAGIC}, runtime, settings::*, transport::{NusbTransport, TransferDirection, TransferObserver, Transport}, CamError, CamResult, TransferOp};
+
+pub use crate::stream::PictureReader;
 
 /// Struct for interacting with the camera.
-pub struct HaCam {
-    interface: nusb::Interface,
-    in_addr: u8,
-    out_addr: u8,
+///
+/// The camera is accessed over USB via `NusbTransport` by default,
+/// a different `Transport` can be provided via `HaCam::from_transport`.
+pub struct HaCam<T = NusbTransport> {
+    transport: T,
+
+    /// Parameters the connection was opened with
+    config: HaCamConfig,
+    /// Serial number of the opened camera, used for finding the same camera when reconnecting
+    serial_number: Option<String>,
+    /// Policy used when reconnecting
+    reconnect_policy: ReconnectPolicy,
 
     /// Default amount of tries
     default_tries: u32,
+
+    /// Receiving buffer size for the keepalive command
+    keepalive_rx_size: usize,
+
+    /// If true, the connection was initialized successfully (see `ensure_connected`)
+    comm_initialized: bool,
+    /// Amount of attempts for initializing the connection
+    init_attempts: u32,
+    /// Delay between the connection initialization attempts
+    init_attempt_interval: std::time::Duration,
+
+    /// Initial delay between soft retries
+    retry_backoff_base: std::time::Duration,
+    /// Maximum delay between soft retries
+    retry_backoff_max: std::time::Duration,
+
+    /// If true, capture commands are refused while the camera is overheating
+    thermal_guard: bool,
+    /// If true, the capture helpers verify the picture transfers (see `verify_picture_transfer`)
+    verify_picture_transfers: bool,
+    /// If true, the capture helpers check that the pictures look like JPEGs (see `util::looks_like_jpeg`)
+    validate_jpegs: bool,
+
+    /// Called whenever the camera reports that it's in power save mode
+    on_power_save: Option<Box<dyn Fn() + Send + Sync>>,
+    /// Maximum amount of connection reinitializations for a single command, when the camera is in power save mode
+    max_power_save_reinits: Option<u32>,
+    /// Notified about every completed bulk transfer
+    transfer_observer: Option<Box<dyn TransferObserver>>,
+
+    /// Live view resolution in effect, `None` if live view isn't running
+    live_view_resolution: Option<LiveViewResolution>,
+    /// If true, live view was started at least once since the camera was opened (see `has_initialized_live_view`)
+    live_view_initialized: bool,
+    /// If true, capturing without initializing live view first is an error (see `set_strict_live_view`)
+    strict_live_view: bool,
+    /// If true, a `LiveViewHandle` was dropped without stopping the live view, which is stopped before the next command
+    pending_live_view_stop: bool,
+    /// If true, recording was confirmed to be started (and not confirmed to be stopped yet)
+    recording: bool,
+    /// Last known photo resolution (read from or written to the camera), used for preallocating pictures
+    photo_resolution: Option<PhotoResolution>,
+    /// The CSW received at the end of the last command
+    last_csw: Option<Csw>,
+    /// Amount of frames received via `get_live_view_frame` since live view was started
+    live_view_frame_count: u64,
+    /// Time at which the previous live view frame was received
+    last_live_view_frame_at: Option<runtime::Instant>,
+    /// Buffer the frames of `get_live_view_frame` are assembled in, kept across the frames
+    live_view_buf: Vec<u8>,
+}
+
+/// Parameters used for opening the USB connection to the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HaCamConfig {
+    /// VID of the USB camera.
+    pub vid: u16,
+    /// PID of the USB camera.
+    pub pid: u16,
+    /// Number of the USB interface to claim (0 by default).
+    pub interface_number: u8,
+    /// Default try count for the entire struct.
+    pub default_tries: u32,
+}
+
+impl Default for HaCamConfig {
+    fn default() -> Self {
+        Self {
+            vid: DEFAULT_VID,
+            pid: DEFAULT_PID,
+            interface_number: 0,
+            default_tries: 3,
+        }
+    }
+}
+
+/// Specifies how `HaCam::reconnect` attempts to reconnect to the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Maximum amount of reconnection attempts.
+    pub max_attempts: u32,
+    /// Delay between the reconnection attempts.
+    pub backoff: std::time::Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: consts::DEFAULT_RECONNECT_ATTEMPTS,
+            backoff: consts::DEFAULT_RECONNECT_BACKOFF,
+        }
+    }
+}
+
+/// A camera hotplug event (see `HaCam::watch_hotplug`).
+#[derive(Debug, Clone)]
+pub enum HotplugEvent {
+    /// The camera was connected, it can be opened now.
+    Connected(nusb::DeviceInfo),
+    /// A previously connected camera was disconnected.
+    /// The ID matches the one of the `DeviceInfo` reported when it was connected.
+    Disconnected(nusb::DeviceId),
+}
+
+/// Command Status Wrapper (CSW), sent by the camera at the end of every command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Csw {
+    /// The check integer of the command this CSW belongs to.
+    pub tag: i32,
+    /// The amount of data which was expected but not transferred.
+    /// For read commands, this is usually non-zero, as the maximum receiving size is requested.
+    pub residue: u32,
+    /// The command status reported by the camera (0 means success).
+    pub status: u8,
+}
+
+impl Csw {
+    /// Length of the CSW in bytes.
+    pub const LEN: usize = 13;
+
+    /// Parses the CSW from the end of the buffer. Returns `None` if the buffer doesn't end with a CSW.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        let csw = buf.get(buf.len().checked_sub(Self::LEN)?..)?;
+
+        if csw[0..4] != RX_HEADER_MAGIC {
+            return None;
+        }
+
+        Some(Self {
+            tag: i32::from_be_bytes(csw[4..8].try_into().unwrap()),
+            residue: u32::from_le_bytes(csw[8..12].try_into().unwrap()),
+            status: csw[12],
+        })
+    }
 }
 
 /// Enum representing the action taken upon the status byte when receiving data from the camera.
-#[derive(Default, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq, Eq)]
 pub enum StatusByteAction {
     #[default]
     /// Default action. Evaluates the status byte (usually the first one) and acts accordingly (either tries to send the command again or returns an error)
@@ -28,24 +173,235 @@ pub enum StatusByteAction {
 }
 
 #[repr(i8)]
-#[derive(Debug, Clone, Copy, int_enum::IntEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, int_enum::IntEnum)]
 /// Represents the thermal status of the camera.
+///
+/// The camera only reports these coarse states, the exact temperature thresholds are unknown.
+/// The byte at offset 4 of the `GET_CAMERA_STATUS` response is this state code, not a temperature
+/// (any other value is rejected as `CamError::InvalidFormat`). None of the known commands reports
+/// the temperature in degrees. The response format of `consts::usb::GET_THERMAL_STATUS` is unknown,
+/// `HaCam::send_raw_read` can be used for exploring it.
+///
+/// The raw byte is converted via `TryFrom<i8>` and back via `From<ThermalStatus> for i8` (both derived by `IntEnum`),
+/// `ThermalStatus::try_from(i8::from(x)) == Ok(x)` holds for every variant.
 pub enum ThermalStatus {
+    /// Normal operating temperature.
     Ok = 0,
+    /// The camera is getting hot, but is still operational.
     OverheatLow = 1,
+    /// The camera is overheating, capturing should be paused until it cools down.
     OverheatHigh = 2,
+    /// The camera is below its normal operating temperature.
     Cold = 3,
 }
 
+impl ThermalStatus {
+    /// Returns `false` if the camera is too hot for taking pictures or recording.
+    pub fn is_capture_safe(&self) -> bool {
+        !matches!(self, Self::OverheatHigh)
+    }
+}
+
+impl std::fmt::Display for ThermalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Ok => "Normal temperature",
+            Self::OverheatLow => "Running hot - consider letting the camera cool down",
+            Self::OverheatHigh => "Overheating - capture disabled until cooldown",
+            Self::Cold => "Too cold - the camera is below its operating temperature",
+        };
+
+        f.write_str(text)
+    }
+}
+
+/// A change of the thermal status, as reported by `ThermalMonitor::update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThermalTransition {
+    pub from: ThermalStatus,
+    pub to: ThermalStatus,
+}
+
+/// Debounces the thermal status readings (see `HaCam::query_thermal_status`),
+/// so a reading at the boundary of two states doesn't make a capture loop flap between them.
+///
+/// A new state is only accepted after it was read a given amount of times in a row.
+#[derive(Debug, Clone)]
+pub struct ThermalMonitor {
+    /// Amount of consecutive readings needed for a transition
+    required: u32,
+    /// The accepted state, `None` before the first reading
+    current: Option<ThermalStatus>,
+    /// The state differing from the accepted one and how many times in a row it was read
+    candidate: Option<(ThermalStatus, u32)>,
+}
+
+impl ThermalMonitor {
+    /// Creates a monitor accepting a new state after `required` consecutive readings (at least 1).
+    pub fn new(required: u32) -> Self {
+        Self {
+            required: required.max(1),
+            current: None,
+            candidate: None,
+        }
+    }
+
+    /// Records a reading. Returns the transition, if the reading completed one.
+    ///
+    /// The first reading is accepted as the initial state right away, without reporting a transition.
+    pub fn update(&mut self, status: ThermalStatus) -> Option<ThermalTransition> {
+        let Some(current) = self.current else {
+            self.current = Some(status);
+            return None;
+        };
+
+        if status == current {
+            self.candidate = None;
+            return None;
+        }
+
+        let count = match self.candidate {
+            Some((candidate, count)) if candidate == status => count + 1,
+            _ => 1,
+        };
+
+        if count < self.required {
+            self.candidate = Some((status, count));
+            return None;
+        }
+
+        self.current = Some(status);
+        self.candidate = None;
+
+        Some(ThermalTransition {
+            from: current,
+            to: status,
+        })
+    }
+
+    /// Returns the accepted state, `None` if there weren't any readings yet.
+    pub fn current(&self) -> Option<ThermalStatus> {
+        self.current
+    }
+
+    /// Returns `false` if the accepted state isn't safe for capturing (see `ThermalStatus::is_capture_safe`).
+    /// Before the first reading, capturing is considered safe.
+    pub fn is_capture_safe(&self) -> bool {
+        self.current.is_none_or(|status| status.is_capture_safe())
+    }
+}
+
+/// Buffer type of the live view frame data.
+/// With the `bytes` feature enabled, this is `bytes::Bytes`, which can be cheaply cloned and shared.
+#[cfg(feature = "bytes")]
+pub type FrameData = bytes::Bytes;
+
+/// Buffer type of the live view frame data.
+/// With the `bytes` feature enabled, this is `bytes::Bytes`, which can be cheaply cloned and shared.
+#[cfg(not(feature = "bytes"))]
+pub type FrameData = Vec<u8>;
+
 #[derive(Debug, Clone)]
-/// Contains the raw live view frame buffer and the frame duration.
+/// Contains the raw live view frame buffer and the frame timing.
 pub struct LiveViewFrame {
+    /// Time it took to fetch the frame.
     pub duration: std::time::Duration,
-    pub data: Vec<u8>,
+    /// Index of the frame, counted from the start of the live view (starting at 0).
+    pub index: u64,
+    /// Time elapsed since the previous frame was received, `None` for the first frame.
+    pub interval: Option<std::time::Duration>,
+    pub data: FrameData,
+}
+
+impl LiveViewFrame {
+    /// Returns the frame rate computed from the interval since the previous frame.
+    pub fn fps(&self) -> Option<f64> {
+        self.interval
+            .filter(|interval| !interval.is_zero())
+            .map(|interval| 1.0 / interval.as_secs_f64())
+    }
+}
+
+/// Handle to a running live view, returned by `HaCam::start_live_view_handle`.
+///
+/// The live view should be stopped via `stop`. As `Drop` can't send the stop command (which is async),
+/// dropping the handle without stopping it only flags the camera, and the live view is stopped
+/// right before the next command is sent (see `HaCam::has_pending_live_view_stop`).
+pub struct LiveViewHandle<'a, T: Transport = NusbTransport> {
+    cam: &'a mut HaCam<T>,
+    stopped: bool,
+}
+
+impl<T: Transport> LiveViewHandle<'_, T> {
+    /// Returns the camera, for example for checking the live view status.
+    pub fn cam(&mut self) -> &mut HaCam<T> {
+        self.cam
+    }
+
+    /// Returns a stream of live view frames, see `HaCam::live_view_stream`.
+    pub fn stream(
+        &mut self,
+    ) -> impl Stream<Item = CamResult<(ThermalStatus, LiveViewFrame)>> + Send + '_ {
+        self.cam.live_view_stream()
+    }
+
+    /// Returns a stream of live view frames, fetched at most `target_fps` times per second,
+    /// see `HaCam::live_view_stream_paced`.
+    pub fn stream_paced(
+        &mut self,
+        target_fps: f32,
+    ) -> impl Stream<Item = CamResult<(ThermalStatus, LiveViewFrame)>> + Send + '_ {
+        self.cam.live_view_stream_paced(target_fps)
+    }
+
+    /// Stops the live view and polls `HaCam::check_live_view_stop_request_status` until it's stopped
+    /// (see `HaCam::stop_live_view_confirmed`).
+    ///
+    /// * `timeout` - If the stream doesn't stop in time, `CamError::Timeout` is returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn stop(mut self, timeout: std::time::Duration) -> CamResult<()> {
+        self.stopped = true;
+
+        self.cam.stop_live_view_confirmed(timeout).await
+    }
+}
+
+impl<T: Transport> Drop for LiveViewHandle<'_, T> {
+    fn drop(&mut self) {
+        if !self.stopped {
+            warn!(
+                "Live view handle dropped without being stopped, stopping the live view before the next command"
+            );
+            self.cam.pending_live_view_stop = true;
+        }
+    }
 }
 
+/// The camera's status, as returned by `HaCam::status_snapshot`.
 #[derive(Debug, Clone)]
+pub struct StatusSnapshot {
+    /// The execution status (see `HaCam::get_camera_status`), its purpose is unknown.
+    pub execution_status: u8,
+    pub thermal_status: ThermalStatus,
+    /// See `HaCam::query_remaining_pic_num`.
+    pub remaining_pic_num: u8,
+    /// See `HaCam::get_camera_info`.
+    pub firmware_version: Option<String>,
+}
+
+/// The camera's versions, as returned by `HaCam::get_versions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Versions {
+    /// See `HaCam::get_camera_info`.
+    pub firmware: Option<String>,
+    /// See `HaCam::get_scsi_version`.
+    pub scsi: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Represents the capture status of a picture.
+///
+/// Two `ThumbnailAvailable` statuses are only equal if all of their fields are equal.
 pub enum CaptureStatus {
     ThumbnailAvailable {
         stored_pic_num: u8,
@@ -57,86 +413,662 @@ pub enum CaptureStatus {
     Captured,
 }
 
+impl CaptureStatus {
+    /// Returns `true` if the picture was captured (`Captured`), so polling the status can stop
+    /// and the picture can be transferred.
+    ///
+    /// ```no_run
+    /// # use hacam_lib_rs::{cam::HaCam, settings::PictureOrientation};
+    /// # async fn capture(cam: &mut HaCam) -> Result<(), hacam_lib_rs::CamError> {
+    /// cam.take_picture(PictureOrientation::Deg0).await?;
+    ///
+    /// while !cam.check_capture_status().await?.is_terminal() {
+    ///     tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    /// }
+    ///
+    /// let (first_part, is_end) = cam.get_partial_picture_buffer(0).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Captured)
+    }
+}
+
+// Not derived, as the hooks can't be printed. No I/O is done, the transport prints only what it already knows
+// (`NusbTransport` prints the interface number and the endpoint addresses, not the interface handle).
+impl<T: std::fmt::Debug> std::fmt::Debug for HaCam<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HaCam")
+            .field("transport", &self.transport)
+            .field("config", &self.config)
+            .field("serial_number", &self.serial_number)
+            .field("default_tries", &self.default_tries)
+            .field("comm_initialized", &self.comm_initialized)
+            .field("live_view_resolution", &self.live_view_resolution)
+            .field("recording", &self.recording)
+            .field("photo_resolution", &self.photo_resolution)
+            .field("last_csw", &self.last_csw)
+            .finish_non_exhaustive()
+    }
+}
+
 impl HaCam {
     /// Opens the USB connection to the camera with default parameters.
     ///
     /// The caller should then use the `initialize_comm` function,
     /// which initializes the data communication to the camera.
     pub fn new() -> CamResult<Self> {
-        Self::new_custom(DEFAULT_VID, DEFAULT_PID, 3)
+        Self::with_config(HaCamConfig::default())
     }
 
-    /// Opens the USB connection to the camera with custom parameters.
+    /// Opens the USB connection to the camera with custom parameters (see `HaCamConfig`).
     ///
     /// The caller should then use the `initialize_comm` function,
     /// which initializes the data communication to the camera.
     ///
-    /// * `vid` - VID of the USB camera.
-    /// * `pid` - VID of the USB camera.
-    /// * `default_tries` - Specify the default try count for the entire struct.
-    fn new_custom(vid: u16, pid: u16, default_tries: u32) -> CamResult<Self> {
+    /// Returns `CamError::EndpointNotFound` if the claimed interface doesn't have bulk IN/OUT endpoints.
+    pub fn with_config(config: HaCamConfig) -> CamResult<Self> {
+        let (interface, serial_number) = Self::open_interface(&config, None)?;
+
+        let (in_addr, out_addr) = Self::detect_endpoints(&interface)?;
+
+        let mut cam = Self::from_transport_with_config(NusbTransport::new(interface, in_addr, out_addr), config);
+        cam.serial_number = serial_number;
+
+        Ok(cam)
+    }
+
+    /// Waits for the camera (with the default VID/PID) to appear and opens it with default parameters.
+    /// Useful when the camera may not be enumerated yet, such as right after boot.
+    ///
+    /// * `timeout` - If the camera doesn't appear in time, `CamError::NoDeviceFound` is returned.
+    /// * `poll` - Interval between the device scans.
+    ///
+    /// The caller should then use the `initialize_comm` function,
+    /// which initializes the data communication to the camera.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn scan_and_open(
+        timeout: std::time::Duration,
+        poll: std::time::Duration,
+    ) -> CamResult<Self> {
+        let HaCamConfig { vid, pid, .. } = HaCamConfig::default();
+
+        let deadline = runtime::Instant::now() + timeout;
+
+        while !nusb::list_devices()?.any(|d| d.vendor_id() == vid && d.product_id() == pid) {
+            if runtime::Instant::now() + poll > deadline {
+                error!("The camera didn't appear in time");
+                return Err(CamError::NoDeviceFound { vid, pid });
+            }
+
+            debug!("The camera isn't connected yet, scanning again...");
+            runtime::sleep(poll).await;
+        }
+
+        Self::new()
+    }
+
+    /// Opens the device specified by the config (and the serial number, if provided) and claims its interface.
+    ///
+    /// Returns the interface and the serial number of the opened device.
+    fn open_interface(
+        config: &HaCamConfig,
+        serial_number: Option<&str>,
+    ) -> CamResult<(nusb::Interface, Option<String>)> {
+        let HaCamConfig { vid, pid, .. } = *config;
+
+        let dev_info = nusb::list_devices()?
+            .find(|d| {
+                d.vendor_id() == vid
+                    && d.product_id() == pid
+                    && serial_number.is_none_or(|serial| d.serial_number() == Some(serial))
+            })
+            .ok_or(CamError::NoDeviceFound { vid, pid })?;
+
+        let device = dev_info.open().map_err(|e| Self::map_open_error(e, vid, pid))?;
+
+        let interface = device
+            .claim_interface(config.interface_number)
+            .map_err(|e| Self::map_open_error(e, vid, pid))?;
+
+        Ok((interface, dev_info.serial_number().map(str::to_owned)))
+    }
+
+    /// Checks if the current user is permitted to open the camera (with the default VID/PID).
+    ///
+    /// On Linux, opening the device fails unless there's a udev rule granting access to it.
+    /// Returns `CamError::PermissionDenied` (with the suggested udev rule) in that case,
+    /// or `CamError::NoDeviceFound` if the camera isn't connected.
+    #[cfg(target_os = "linux")]
+    pub fn check_permissions() -> CamResult<()> {
+        let HaCamConfig { vid, pid, .. } = HaCamConfig::default();
+
         let dev_info = nusb::list_devices()?
             .find(|d| d.vendor_id() == vid && d.product_id() == pid)
             .ok_or(CamError::NoDeviceFound { vid, pid })?;
 
-        let device = dev_info.open()?;
+        dev_info.open().map_err(|e| Self::map_open_error(e, vid, pid))?;
 
-        let interface = device.claim_interface(0)?;
+        Ok(())
+    }
 
-        Ok(Self {
-            interface,
-            default_tries,
-            in_addr: ENDPOINT_IN_ADDR,
-            out_addr: ENDPOINT_OUT_ADDR,
-        })
+    /// Maps the platform-specific errors of opening the device to more descriptive errors:
+    ///
+    /// * `EACCES` on Linux to `CamError::PermissionDenied`.
+    /// * The "unsupported" error on Windows (the device isn't bound to WinUSB) to `CamError::DriverNotWinUsb`.
+    fn map_open_error(e: std::io::Error, vid: u16, pid: u16) -> CamError {
+        if cfg!(windows) && e.kind() == std::io::ErrorKind::Unsupported {
+            warn!("Couldn't open the device, the bound driver isn't WinUSB ({e})");
+
+            CamError::DriverNotWinUsb { vid, pid }
+        } else if cfg!(target_os = "linux") && e.kind() == std::io::ErrorKind::PermissionDenied {
+            let hint = format!(
+                "add the udev rule `SUBSYSTEM==\"usb\", ATTRS{{idVendor}}==\"{vid:04x}\", \
+                 ATTRS{{idProduct}}==\"{pid:04x}\", MODE=\"0666\"` to /etc/udev/rules.d/ and replug the camera"
+            );
+
+            CamError::PermissionDenied { vid, pid, hint }
+        } else {
+            e.into()
+        }
+    }
+
+    /// Returns the number of the claimed USB interface.
+    pub fn interface_number(&self) -> u8 {
+        self.transport.interface().interface_number()
+    }
+
+    /// Returns the address of the bulk IN endpoint in use (detected from the interface descriptor).
+    pub fn in_endpoint(&self) -> u8 {
+        self.transport.in_endpoint()
+    }
+
+    /// Returns the address of the bulk OUT endpoint in use (detected from the interface descriptor).
+    pub fn out_endpoint(&self) -> u8 {
+        self.transport.out_endpoint()
+    }
+
+    /// Sets the policy used by `reconnect`.
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self.reconnect_policy = policy;
+    }
+
+    /// Reopens the USB connection to the same camera (matched by the serial number, if the camera reports one)
+    /// and reinitializes the communication.
+    ///
+    /// This is meant to be called after the camera was disconnected (see `CamError::is_disconnect`),
+    /// as the handle is unusable afterwards. The reconnection is attempted
+    /// according to the `ReconnectPolicy` (see `set_reconnect_policy`), the last error is returned if all attempts fail.
+    ///
+    /// The live view state is reset, so the live view has to be started again.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn reconnect(&mut self) -> CamResult<()> {
+        let ReconnectPolicy {
+            max_attempts,
+            backoff,
+        } = self.reconnect_policy;
+
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            info!("Attempting to reconnect to the camera... (Attempt {attempt}/{max_attempts})");
+
+            match self.try_reconnect().await {
+                Ok(()) => {
+                    info!("Reconnected to the camera successfully!");
+                    return Ok(());
+                }
+                Err(e) if attempt < max_attempts => {
+                    warn!("Couldn't reconnect to the camera ({e}), trying again...");
+                    runtime::sleep(backoff).await;
+                }
+                Err(e) => {
+                    error!("Couldn't reconnect to the camera ({e}), giving up");
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    async fn try_reconnect(&mut self) -> CamResult<()> {
+        let (interface, _) = Self::open_interface(&self.config, self.serial_number.as_deref())?;
+
+        let (in_addr, out_addr) = Self::detect_endpoints(&interface)?;
+
+        self.transport = NusbTransport::new(interface, in_addr, out_addr);
+
+        self.live_view_resolution = None;
+        self.live_view_initialized = false;
+        self.pending_live_view_stop = false;
+        self.recording = false;
+        self.live_view_frame_count = 0;
+        self.last_live_view_frame_at = None;
+        self.last_csw = None;
+        self.comm_initialized = false;
+
+        self.initialize_comm().await
+    }
+
+    /// Finds the first bulk IN and bulk OUT endpoint addresses of the interface.
+    ///
+    /// Falls back to `ENDPOINT_IN_ADDR` and `ENDPOINT_OUT_ADDR` if the interface descriptors can't be read.
+    fn detect_endpoints(interface: &nusb::Interface) -> CamResult<(u8, u8)> {
+        let Some(alt_setting) = interface.descriptors().find(|a| a.alternate_setting() == 0) else {
+            warn!("Couldn't read the interface descriptors, using the default endpoint addresses");
+            return Ok((ENDPOINT_IN_ADDR, ENDPOINT_OUT_ADDR));
+        };
+
+        let find_bulk_endpoint = |direction| {
+            alt_setting
+                .endpoints()
+                .find(|e| e.transfer_type() == EndpointType::Bulk && e.direction() == direction)
+                .map(|e| e.address())
+        };
+
+        match (find_bulk_endpoint(Direction::In), find_bulk_endpoint(Direction::Out)) {
+            (Some(in_addr), Some(out_addr)) => {
+                debug!("Detected bulk endpoints: IN {in_addr:#04X}, OUT {out_addr:#04X}");
+                Ok((in_addr, out_addr))
+            }
+            _ => Err(CamError::EndpointNotFound {
+                interface: interface.interface_number(),
+            }),
+        }
+    }
+}
+
+impl<T: Transport> HaCam<T> {
+    /// Creates the camera from a custom transport (such as a mock for testing), with default parameters.
+    ///
+    /// The caller should then use the `initialize_comm` function,
+    /// which initializes the data communication to the camera.
+    pub fn from_transport(transport: T) -> Self {
+        Self::from_transport_with_config(transport, HaCamConfig::default())
+    }
+
+    /// Creates the camera from a custom transport with custom parameters.
+    /// The VID/PID and the interface number of the config are only used when reconnecting.
+    pub fn from_transport_with_config(transport: T, config: HaCamConfig) -> Self {
+        Self {
+            transport,
+            default_tries: config.default_tries,
+            keepalive_rx_size: consts::KEEPALIVE_RX_BUF_SIZE,
+            config,
+            serial_number: None,
+            reconnect_policy: ReconnectPolicy::default(),
+            comm_initialized: false,
+            init_attempts: config.default_tries,
+            init_attempt_interval: consts::INIT_ATTEMPT_INTERVAL,
+            retry_backoff_base: consts::DEFAULT_RETRY_BACKOFF_BASE,
+            retry_backoff_max: consts::DEFAULT_RETRY_BACKOFF_MAX,
+            thermal_guard: false,
+            verify_picture_transfers: false,
+            validate_jpegs: false,
+            on_power_save: None,
+            max_power_save_reinits: None,
+            transfer_observer: None,
+            live_view_resolution: None,
+            live_view_initialized: false,
+            pending_live_view_stop: false,
+            strict_live_view: false,
+            recording: false,
+            photo_resolution: None,
+            last_csw: None,
+            live_view_frame_count: 0,
+            last_live_view_frame_at: None,
+            live_view_buf: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns a mutable reference to the transport.
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Sets the exponential backoff used between soft retries of a command
+    /// (when the camera signals that the command should be sent again).
+    ///
+    /// * `base` - Delay before the first retry, doubled for every following retry.
+    /// * `max` - Maximum delay between retries.
+    pub fn set_retry_backoff(&mut self, base: std::time::Duration, max: std::time::Duration) {
+        self.retry_backoff_base = base;
+        self.retry_backoff_max = max;
+    }
+
+    /// Sets the amount of attempts and the delay between them used by `initialize_comm`.
+    /// Slow-to-wake cameras may need a longer interval to come out of power save mode.
+    ///
+    /// * `attempts` - Amount of attempts, `default_tries` of the config by default.
+    /// * `interval` - Delay between attempts, `consts::INIT_ATTEMPT_INTERVAL` by default.
+    pub fn set_init_attempts(&mut self, attempts: u32, interval: std::time::Duration) {
+        self.init_attempts = attempts;
+        self.init_attempt_interval = interval;
+    }
+
+    /// Enables or disables the thermal guard (disabled by default).
+    ///
+    /// When enabled, `take_picture`, `start_recording` and `start_live_view` first query the thermal status
+    /// of the camera and return `CamError::Overheated` if it's unsafe to capture (see `ThermalStatus::is_capture_safe`).
+    /// Note that this costs an extra USB round-trip for each of these commands.
+    pub fn set_thermal_guard(&mut self, enabled: bool) {
+        self.thermal_guard = enabled;
+    }
+
+    /// Enables or disables the picture transfer verification in the `CamUtil` capture helpers (disabled by default).
+    ///
+    /// When enabled, `verify_picture_transfer` is called after the last partial picture buffer is received.
+    /// If the camera doesn't confirm the transfer, the picture is read again from the start (up to the default
+    /// amount of tries), and `CamError::PictureVerification` is returned if it still can't be confirmed.
+    /// The progress reported by `take_picture_and_get_with_progress` starts over when the picture is read again.
+    pub fn set_picture_transfer_verification(&mut self, enabled: bool) {
+        self.verify_picture_transfers = enabled;
+    }
+
+    /// Enables or disables the strict live view check (disabled by default).
+    ///
+    /// The camera returns an all black picture unless live view was started before capturing.
+    /// When enabled, `take_picture` returns `CamError::LiveViewNotInitialized` if live view wasn't started
+    /// since the camera was opened (see `has_initialized_live_view`), and so do the `CamUtil` capture helpers
+    /// instead of initializing live view themselves.
+    pub fn set_strict_live_view(&mut self, enabled: bool) {
+        self.strict_live_view = enabled;
+    }
+
+    /// Returns `true` if the strict live view check is enabled (see `set_strict_live_view`).
+    pub(crate) fn is_strict_live_view(&self) -> bool {
+        self.strict_live_view
+    }
+
+    /// Returns `true` if the capture helpers verify the picture transfers.
+    pub(crate) fn verifies_picture_transfers(&self) -> bool {
+        self.verify_picture_transfers
+    }
+
+    /// Enables or disables the JPEG validation in the `CamUtil` capture helpers (disabled by default).
+    ///
+    /// When enabled, every received picture is checked with `util::looks_like_jpeg`. A picture which doesn't
+    /// pass is read again from the start (up to the default amount of tries), and `CamError::CorruptPicture`
+    /// is returned if it still doesn't pass.
+    pub fn set_jpeg_validation(&mut self, enabled: bool) {
+        self.validate_jpegs = enabled;
+    }
+
+    /// Returns `true` if the capture helpers validate the received pictures.
+    pub(crate) fn validates_jpegs(&self) -> bool {
+        self.validate_jpegs
+    }
+
+    /// Returns the default amount of tries.
+    pub(crate) fn default_tries(&self) -> u32 {
+        self.default_tries
+    }
+
+    /// Sets the maximum amount of connection reinitializations for a single command, when the camera reports
+    /// that it's in power save mode. The reinitializations then don't count towards the soft retries of the command.
+    /// If the camera is still in power save mode afterwards, `CamError::PowerSave` is returned.
+    /// Set it to `Some(0)` to fail fast without reinitializing the connection.
+    ///
+    /// If `None` (the default), every reinitialization uses up one of the tries of the command
+    /// and `CamError::SendCommand` is returned once they run out.
+    pub fn set_max_power_save_reinits(&mut self, max_reinits: Option<u32>) {
+        self.max_power_save_reinits = max_reinits;
+    }
+
+    /// Sets the hook called whenever the camera reports that it's in power save mode (status byte 255),
+    /// right before the connection is reinitialized. Useful for monitoring, or for increasing the keepalive frequency.
+    ///
+    /// Provide `None::<fn()>` to remove the hook.
+    pub fn set_on_power_save(&mut self, on_power_save: Option<impl Fn() + Send + Sync + 'static>) {
+        self.on_power_save = on_power_save.map(|f| Box::new(f) as Box<dyn Fn() + Send + Sync>);
+    }
+
+    /// Sets the observer notified about the direction, size and duration of every completed bulk transfer
+    /// (see `TransferObserver`). Useful for collecting transfer metrics without enabling logging.
+    ///
+    /// Provide `None::<fn(_, _, _)>` to remove the observer.
+    pub fn set_transfer_observer(&mut self, observer: Option<impl TransferObserver + 'static>) {
+        self.transfer_observer = observer.map(|o| Box::new(o) as Box<dyn TransferObserver>);
+    }
+
+    /// Sends the data to the bulk OUT endpoint, notifying the transfer observer (if set).
+    async fn bulk_out(&mut self, data: Vec<u8>) -> CamResult<()> {
+        let len = data.len();
+        let started_at = self.transfer_observer.as_ref().map(|_| runtime::Instant::now());
+
+        self.transport.bulk_out(data).await?;
+
+        if let (Some(observer), Some(started_at)) = (&self.transfer_observer, started_at) {
+            observer.on_transfer(TransferDirection::Out, len, started_at.elapsed());
+        }
+
+        Ok(())
+    }
+
+    /// Receives up to `len` bytes from the bulk IN endpoint, notifying the transfer observer (if set).
+    async fn bulk_in(&mut self, buf: Vec<u8>, len: usize) -> CamResult<Vec<u8>> {
+        let started_at = self.transfer_observer.as_ref().map(|_| runtime::Instant::now());
+
+        let data = self.transport.bulk_in(buf, len).await?;
+
+        if let (Some(observer), Some(started_at)) = (&self.transfer_observer, started_at) {
+            observer.on_transfer(TransferDirection::In, data.len(), started_at.elapsed());
+        }
+
+        Ok(data)
+    }
+
+    /// Returns `CamError::Overheated` if the thermal guard is enabled and the camera is too hot for capturing.
+    async fn check_thermal_guard(&mut self) -> CamResult<()> {
+        if !self.thermal_guard {
+            return Ok(());
+        }
+
+        let status = self.query_thermal_status().await?;
+
+        if status.is_capture_safe() {
+            Ok(())
+        } else {
+            warn!("Refusing to capture, the camera is overheating ({status:?})");
+            Err(CamError::Overheated { status })
+        }
+    }
+
+    /// Returns the delay before the given soft retry (indexed from 0).
+    fn retry_backoff(&self, retry_no: u32) -> std::time::Duration {
+        self.retry_backoff_base
+            .saturating_mul(2u32.saturating_pow(retry_no))
+            .min(self.retry_backoff_max)
+    }
+
+    /// Sends the command which opens the connection, retrying transient USB transfer errors
+    /// (such as a stall right after the camera is plugged in) up to `default_tries` times.
+    async fn send_open_conn_command(&mut self) -> CamResult<Vec<u8>> {
+        let mut retry_no = 0;
+
+        loop {
+            match self
+                .read_data_unchecked(&consts::scsi::OPEN_CONN_COMMAND)
+                .await
+            {
+                Err(e @ CamError::UsbTransfer(_))
+                    if e.is_retryable() && retry_no < self.default_tries =>
+                {
+                    warn!(
+                        "USB transfer failed while initializing the connection ({e}), trying again... (Retry {}/{})",
+                        retry_no + 1,
+                        self.default_tries
+                    );
+
+                    runtime::sleep(self.retry_backoff(retry_no)).await;
+                    retry_no += 1;
+                }
+                res => return res,
+            }
+        }
     }
 
     /// Attempts to initialize communication to the camera.
+    ///
+    /// The status byte of the response is handled as follows:
+    /// * `0` - The connection was initialized.
+    /// * `1` - The camera isn't ready yet (for example waking up from power save mode), the command is attempted again.
+    /// * Anything else is returned as `CamError::ConnInit`.
+    ///
+    /// Transient USB transfer errors (see `CamError::is_retryable`) are retried up to `default_tries` times
+    /// with the retry backoff (see `set_retry_backoff`), without using up the attempts.
+    ///
+    /// The amount of attempts and the delay between them can be set via `set_init_attempts`.
+    /// This always sends the command - to only initialize the connection when needed, use `ensure_connected`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn initialize_comm(&mut self) -> CamResult<()> {
-        for attempt_no in 0..self.default_tries {
-            let out = self
-                .read_data_unchecked(&consts::scsi::OPEN_CONN_COMMAND)
-                .await?;
+        let mut last_status = None;
+        let mut attempts = 0;
+
+        for attempt_no in 0..self.init_attempts {
+            if attempt_no > 0 {
+                runtime::sleep(self.init_attempt_interval).await;
+            }
 
-            match out[0] {
+            let out = self.send_open_conn_command().await?;
+
+            attempts += 1;
+
+            let status = *out.first().ok_or(CamError::InvalidLength {
+                expected: 1,
+                received: 0,
+            })?;
+
+            match status {
                 0 => {
                     info!("Connection initialized successfully!");
+                    self.comm_initialized = true;
                     return Ok(());
                 }
                 1 => warn!(
-                    "Connection initialized unsuccessfully, trying again... (Attempt {attempt_no}/{})",
-                    self.default_tries
+                    "Camera reported it isn't ready (status 1), trying again... (Attempt {attempts}/{})",
+                    self.init_attempts
                 ),
                 other => {
                     error!("Unable to initialize connection. Status code: {other}.");
 
                     return Err(CamError::ConnInit {
-                        tries: 1,
+                        tries: attempts,
                         status_code: other as u32,
                     });
                 }
             }
 
-            tokio::time::sleep(consts::INIT_ATTEMPT_INTERVAL).await;
+            last_status = Some(status);
         }
 
         error!(
-            "Unable to initialize connection, reached max attempts ({}).",
-            self.default_tries
+            "Unable to initialize connection, reached max attempts ({attempts}), last status code: {last_status:?}."
         );
         Err(CamError::ConnInit {
-            tries: self.default_tries,
-            status_code: 1,
+            tries: attempts,
+            status_code: last_status.unwrap_or(1) as u32,
+        })
+    }
+
+    /// Sends the `APP_CONN_COMMAND`, which the original app sends after initializing the communication.
+    ///
+    /// The command isn't part of `initialize_comm`, as the camera works without it (live view, capture, settings)
+    /// and it isn't known yet which features it unlocks. Its layout mirrors `CLOSE_CONNECTION`
+    /// (with byte 3 cleared and byte 9 set), so it likely opens an app-level session, with bytes 12-13
+    /// (300, little-endian) possibly being the session timeout in seconds.
+    /// Try sending it if the camera misbehaves (for example returns black live view frames).
+    ///
+    /// The status byte of the response is handled like in `initialize_comm`, except that it's only sent once -
+    /// a non-zero status is returned as `CamError::ConnInit`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn app_connect(&mut self) -> CamResult<()> {
+        let out = self
+            .read_data_unchecked(&consts::scsi::APP_CONN_COMMAND)
+            .await?;
+
+        match out.first() {
+            Some(0) => {
+                info!("App connection established");
+                Ok(())
+            }
+            Some(&status) => {
+                error!("Unable to establish the app connection. Status code: {status}.");
+                Err(CamError::ConnInit {
+                    tries: 1,
+                    status_code: status as u32,
+                })
+            }
+            None => Err(CamError::InvalidLength {
+                expected: 1,
+                received: 0,
+            }),
+        }
+    }
+
+    /// Initializes the communication to the camera, unless it was already initialized and the camera
+    /// still responds (see `ping`). Safe to call repeatedly, as opposed to `initialize_comm`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn ensure_connected(&mut self) -> CamResult<()> {
+        if self.comm_initialized && self.ping().await? {
+            debug!("Connection is already initialized");
+            return Ok(());
+        }
+
+        self.initialize_comm().await
+    }
+
+    /// Brings the camera fully up, for example out of its power save mode.
+    ///
+    /// `initialize_comm` only performs the connection handshake, which the camera may accept
+    /// while it's still waking up, so the first commands sent afterwards can still fail.
+    /// This function initializes the communication and then pings the camera (see `ping`)
+    /// until it responds to a regular command, using the same amount of attempts and delay as `initialize_comm`
+    /// (see `set_init_attempts`).
+    ///
+    /// Returns `CamError::Timeout` if the camera doesn't respond to any of the pings.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn wake(&mut self) -> CamResult<()> {
+        self.initialize_comm().await?;
+
+        for attempt_no in 0..self.init_attempts {
+            if attempt_no > 0 {
+                runtime::sleep(self.init_attempt_interval).await;
+            }
+
+            if self.ping().await? {
+                info!("Camera is awake");
+                return Ok(());
+            }
+
+            debug!(
+                "Camera isn't responding yet (Attempt {}/{})",
+                attempt_no + 1,
+                self.init_attempts
+            );
+        }
+
+        error!("Camera didn't respond after initializing the connection");
+        Err(CamError::Timeout {
+            op: TransferOp::StatusPoll,
         })
     }
 
     /// Sends the keepalive command with the default keepalive timeout.
     /// The keepalive command should be sent every 500ms (the default keepalive interval),
     /// when there are no other transfers.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn send_keepalive(&mut self) -> CamResult<()> {
-        let max_recv_size = consts::KEEPALIVE_RX_BUF_SIZE;
+        self.stop_pending_live_view().await;
 
-        let req_buf = nusb::transfer::RequestBuffer::new(max_recv_size);
+        let max_recv_size = self.keepalive_rx_size;
 
         let cmd = Self::make_cmd_header(
             &consts::scsi::KEEP_ALIVE_COMMAND,
@@ -145,37 +1077,65 @@ impl HaCam {
             Self::rand_int(),
         )?;
 
-        tokio::time::timeout(
+        with_timeout(
+            TransferOp::Keepalive,
             consts::KEEPALIVE_TIMEOUT,
-            self.interface.bulk_out(self.out_addr, cmd),
+            self.bulk_out(cmd),
         )
-        .await?
-        .into_result()?;
+        .await??;
 
-        let res = tokio::time::timeout(
+        let res = with_timeout(
+            TransferOp::Keepalive,
             consts::KEEPALIVE_TIMEOUT,
-            self.interface.bulk_in(self.in_addr, req_buf),
+            self.bulk_in(Vec::new(), max_recv_size),
         )
-        .await?
-        .into_result()?;
+        .await??;
 
-        let status_byte = res.first().ok_or(CamError::InvalidLength {
-            expected: 1,
-            received: 0,
-        })?;
-
-        match status_byte {
+        match parse_keepalive_status(&res)? {
             0 => Ok(()),
             other => {
                 error!("Error in keepalive! Received unknown/errorous status code {other}.");
                 Err(CamError::Keepalive {
-                    status_code: *other as u32,
+                    status_code: other as u32,
                 })
             }
         }
     }
 
+    /// Sets the receiving buffer size for the keepalive command (`consts::KEEPALIVE_RX_BUF_SIZE` by default).
+    pub fn set_keepalive_rx_size(&mut self, size: usize) {
+        self.keepalive_rx_size = size;
+    }
+
+    /// Checks if the camera is responsive, by sending the lightweight `GET_SCSI_VERSION` command.
+    ///
+    /// Returns `true` if the camera responded with a valid CSW-framed response within the default timeout.
+    /// Unlike `send_keepalive`, a failure (including a timeout or an USB error) is not returned as an error,
+    /// but as `false`, which makes this suitable for watchdog loops.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn ping(&mut self) -> CamResult<bool> {
+        self.last_csw = None;
+
+        match self
+            .read_data(
+                &consts::usb::GET_SCSI_VERSION,
+                consts::DEFAULT_MAX_RECV_SIZE,
+                consts::DEFAULT_TRANSFER_TIMEOUT,
+            )
+            .await
+        {
+            Ok(_) => Ok(self.last_csw.is_some()),
+            Err(e) => {
+                debug!("Camera didn't respond to ping ({e})");
+                Ok(false)
+            }
+        }
+    }
+}
+
+impl HaCam {
     /// Resets the USB camera device via an USB control transfer.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn reset_usb(&mut self) -> CamResult<()> {
         // The original app uses the bmRequestType value of 0x21 (33),
         // in binary represented as 0b0010_0001.
@@ -192,11 +1152,12 @@ impl HaCam {
             recipient: Recipient::Interface,
             request: 255,
             value: 0,
-            index: self.interface.interface_number() as u16,
+            index: self.transport.interface().interface_number() as u16,
             data: &[],
         };
 
-        self.interface
+        self.transport
+            .interface()
             .control_out(ctrl)
             .await
             .into_result()
@@ -206,24 +1167,16 @@ impl HaCam {
 
         Ok(())
     }
+}
 
+impl<T: Transport> HaCam<T> {
     /// Internal message checking function.
-    fn is_msg_csw(buf: &[u8], check_int: i32) -> bool {
-        let len = buf.len();
-
-        if len < 13 {
-            return false;
-        }
-
-        let is_first_part_ok = buf[len - 13..=len - 10] == RX_HEADER_MAGIC;
-
-        if check_int == 0 {
-            is_first_part_ok
-        } else {
-            let is_second_part_ok = buf[len - 9..=len - 6] == check_int.to_be_bytes();
+    ///
+    /// Returns the parsed CSW if the buffer ends with one (and its tag matches `check_int`, unless it's 0).
+    fn parse_msg_csw(buf: &[u8], check_int: i32) -> Option<Csw> {
+        let csw = Csw::parse(buf)?;
 
-            is_first_part_ok && is_second_part_ok
-        }
+        (check_int == 0 || csw.tag == check_int).then_some(csw)
     }
 
     /// Creates the header for a command.
@@ -273,7 +1226,7 @@ impl HaCam {
     ///
     /// Returns the raw buffer sent by the camera.
     async fn read_data_unchecked(&mut self, cmd_bfr: &[i8]) -> CamResult<Vec<u8>> {
-        let req_buf = nusb::transfer::RequestBuffer::new(consts::DEFAULT_MAX_RECV_SIZE);
+        self.stop_pending_live_view().await;
 
         let out_buf: Vec<u8> = Self::make_cmd_header(
             cmd_bfr,
@@ -282,16 +1235,11 @@ impl HaCam {
             Self::rand_int(),
         )?;
 
-        self.interface
-            .bulk_out(self.out_addr, out_buf)
-            .await
-            .into_result()?;
+        self.bulk_out(out_buf).await?;
 
         let in_buf = self
-            .interface
-            .bulk_in(self.in_addr, req_buf)
-            .await
-            .into_result()?;
+            .bulk_in(Vec::new(), consts::DEFAULT_MAX_RECV_SIZE)
+            .await?;
 
         Ok(in_buf)
     }
@@ -302,53 +1250,111 @@ impl HaCam {
     /// while also using the checksum messages.
     ///
     /// * `cmd_bfr` - The command buffer (such as TAKE_PICTURE, GET_CAMERA_INFO). Usually 16 bytes.
+    /// * `max_recv` - Maximum amount of data received. Usually `consts::DEFAULT_MAX_RECV_SIZE`.
     /// * `timeout` - Specifies the transfer timeout.
     ///
-    /// Returns the raw buffer sent by the camera.
-    async fn read_data(&mut self, cmd_bfr: &[i8], timeout: std::time::Duration) -> CamResult<Vec<u8>> {
-        let mut ret_buf: Vec<u8> = Vec::with_capacity(consts::DEFAULT_MAX_RECV_SIZE);
+    /// Returns the raw buffer sent by the camera, or `CamError::InvalidLength`
+    /// if the camera sent more than `max_recv` bytes (the data would be truncated).
+    /// Zero-length packets are skipped, `CamError::InvalidFormat` is returned if more than
+    /// `consts::MAX_ZERO_LENGTH_READS` of them are received in a row.
+    async fn read_data(
+        &mut self,
+        cmd_bfr: &[i8],
+        max_recv: usize,
+        timeout: std::time::Duration,
+    ) -> CamResult<Vec<u8>> {
+        self.stop_pending_live_view().await;
+
+        let mut ret_buf: Vec<u8> = Vec::with_capacity(max_recv);
 
         let check_int = Self::rand_int();
 
-        let out_buf: Vec<u8> = Self::make_cmd_header(
-            cmd_bfr,
-            consts::DEFAULT_MAX_RECV_SIZE as i32,
-            true,
-            check_int,
-        )?;
+        let out_buf: Vec<u8> = Self::make_cmd_header(cmd_bfr, max_recv as i32, true, check_int)?;
+
+        with_timeout(
+            TransferOp::CmdWrite,
+            timeout,
+            self.bulk_out(out_buf),
+        )
+        .await??;
 
-        tokio::time::timeout(timeout, self.interface.bulk_out(self.out_addr, out_buf))
-            .await?
-            .into_result()?;
+        // The buffer of every completed transfer is reclaimed and reused for the next chunk,
+        // so only one chunk buffer is allocated for the whole read.
+        let mut req_buf = Vec::new();
+        // Amount of data which didn't fit into `max_recv`.
+        let mut dropped = 0;
+
+        // Amount of consecutive zero-length packets received.
+        let mut zero_len_reads = 0;
 
         loop {
-            let req_buf = nusb::transfer::RequestBuffer::new(consts::DEFAULT_CHUNK_SIZE);
+            let in_tmp_buf = with_timeout(
+                TransferOp::ChunkRead,
+                timeout,
+                self.bulk_in(req_buf, consts::DEFAULT_CHUNK_SIZE),
+            )
+            .await??;
 
-            let in_tmp_buf =
-                tokio::time::timeout(timeout, self.interface.bulk_in(self.in_addr, req_buf))
-                    .await?
-                    .into_result()?;
+            // A zero-length packet carries neither data nor the CSW, so it's skipped
+            // (unless the camera keeps sending them).
+            if in_tmp_buf.is_empty() {
+                zero_len_reads += 1;
 
-            if Self::is_msg_csw(&in_tmp_buf, check_int) {
-                if in_tmp_buf.len() > 13 {
-                    ret_buf.extend(&in_tmp_buf[..in_tmp_buf.len() - 13]);
+                if zero_len_reads > consts::MAX_ZERO_LENGTH_READS {
+                    error!("Received {zero_len_reads} zero-length packets in a row");
+                    return Err(CamError::InvalidFormat);
                 }
 
+                debug!("Received a zero-length packet, reading again");
+                req_buf = in_tmp_buf;
+                continue;
+            }
+
+            zero_len_reads = 0;
+
+            if let Some(csw) = Self::parse_msg_csw(&in_tmp_buf, check_int) {
+                let data_len = in_tmp_buf.len().saturating_sub(Csw::LEN);
+
+                if dropped == 0 && ret_buf.len() + data_len <= max_recv {
+                    ret_buf.extend(&in_tmp_buf[..data_len]);
+                } else {
+                    dropped += data_len;
+                }
+
+                debug!("Received CSW: {csw:?}");
+
+                if csw.status != 0 {
+                    warn!("Camera reported a non-zero CSW status ({}) for a read command", csw.status);
+                }
+
+                self.last_csw = Some(csw);
+
                 break;
-            } else {
-                if in_tmp_buf.len() + ret_buf.len() > consts::DEFAULT_MAX_RECV_SIZE {
+            } else if dropped > 0 || in_tmp_buf.len() + ret_buf.len() > max_recv {
+                if dropped == 0 {
                     error!(
                         "Received too much data! in_tmp_buf: {}, ret_buf: {}, max_recv_size: {}",
                         in_tmp_buf.len(),
                         ret_buf.len(),
-                        consts::DEFAULT_MAX_RECV_SIZE
+                        max_recv
                     );
-
-                    break;
                 }
 
-                ret_buf.extend(in_tmp_buf);
+                // The rest of the data is still drained up to the CSW, so the next command
+                // doesn't receive the leftovers of this one.
+                dropped += in_tmp_buf.len();
+            } else {
+                ret_buf.extend_from_slice(&in_tmp_buf);
             }
+
+            req_buf = in_tmp_buf;
+        }
+
+        if dropped > 0 {
+            return Err(CamError::InvalidLength {
+                expected: max_recv,
+                received: ret_buf.len() + dropped,
+            });
         }
 
         Ok(ret_buf)
@@ -366,31 +1372,49 @@ impl HaCam {
         data_bfr: Vec<u8>,
         timeout: std::time::Duration,
     ) -> CamResult<()> {
+        self.stop_pending_live_view().await;
+
         let check_int = Self::rand_int();
 
         let cmd_header = Self::make_cmd_header(cmd_bfr, data_bfr.len() as i32, false, check_int)?;
 
-        tokio::time::timeout(timeout, self.interface.bulk_out(self.out_addr, cmd_header))
-            .await?
-            .into_result()?;
+        with_timeout(
+            TransferOp::CmdWrite,
+            timeout,
+            self.bulk_out(cmd_header),
+        )
+        .await??;
 
         for data_chunk in data_bfr.chunks(consts::DEFAULT_CHUNK_SIZE) {
-            tokio::time::timeout(
+            with_timeout(
+                TransferOp::DataWrite,
                 timeout,
-                self.interface.bulk_out(self.out_addr, data_chunk.to_vec()),
+                self.bulk_out(data_chunk.to_vec()),
             )
-            .await?
-            .into_result()?;
+            .await??;
         }
 
-        let req_buf = nusb::transfer::RequestBuffer::new(consts::DEFAULT_CHUNK_SIZE);
+        let received_buf = with_timeout(
+            TransferOp::ChunkRead,
+            timeout,
+            self.bulk_in(Vec::new(), consts::DEFAULT_CHUNK_SIZE),
+        )
+        .await??;
+
+        if let Some(csw) = Self::parse_msg_csw(&received_buf, check_int) {
+            self.last_csw = Some(csw);
 
-        let received_buf =
-            tokio::time::timeout(timeout, self.interface.bulk_in(self.in_addr, req_buf))
-                .await?
-                .into_result()?;
+            if csw.status != 0 {
+                warn!("Camera reported a non-zero CSW status ({}) for a write command", csw.status);
+            }
+
+            if csw.residue != 0 {
+                error!("Couldn't write data: the camera didn't accept {} bytes", csw.residue);
+                return Err(CamError::PartialTransfer {
+                    residue: csw.residue,
+                });
+            }
 
-        if Self::is_msg_csw(&received_buf, check_int) {
             Ok(())
         } else {
             error!("Couldn't write data: unknown received data (non-CSW)");
@@ -404,7 +1428,7 @@ impl HaCam {
     /// * `action` - Picks the `StatusByteAction`. This affects if the command is either retried, retried but only if it is in power saving mode
     ///   or if the buffer is returned raw. This is useful for commands such as `GET_REMAINING_PIC_NUM`, which use the status byte
     ///   as the return value.
-    /// * `retries` - Number of "soft retries" (retrying only if we fail not due to USB issues)
+    /// * `timeout` - Transfer timeout.
     ///
     /// Returns the raw buffer returned by the camera.
     pub async fn send_custom_read_command(
@@ -413,10 +1437,69 @@ impl HaCam {
         action: StatusByteAction,
         timeout: std::time::Duration,
     ) -> CamResult<Vec<u8>> {
-        let tries = 1 + self.default_tries;
+        self.send_custom_read_command_with_retries(cmd, action, timeout, None)
+            .await
+    }
+
+    /// Same as `send_custom_read_command`, but the amount of soft retries can be overridden for this call.
+    ///
+    /// * `retries` - Number of "soft retries" (retrying only if we fail not due to USB issues).
+    ///   If `None`, the default try count of the struct is used.
+    ///
+    /// The connection is reinitialized whenever the camera reports that it's in power save mode,
+    /// even if `retries` is 0. The reinitialization uses up one of the tries, so the command
+    /// is only sent again if there are tries left, unless a separate budget of reinitializations
+    /// is set via `set_max_power_save_reinits`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(cmd = ?&cmd[..cmd.len().min(3)], ?action))
+    )]
+    pub async fn send_custom_read_command_with_retries(
+        &mut self,
+        cmd: &[i8],
+        action: StatusByteAction,
+        timeout: std::time::Duration,
+        retries: Option<u32>,
+    ) -> CamResult<Vec<u8>> {
+        self.send_read_command(cmd, action, consts::DEFAULT_MAX_RECV_SIZE, timeout, retries)
+            .await
+    }
 
-        for try_attempt in 0..tries {
-            let res = self.read_data(cmd, timeout).await;
+    /// Same as `send_custom_read_command`, but with a custom maximum amount of received data.
+    /// Useful for larger single-shot reads, which would otherwise fail with `CamError::InvalidLength`.
+    ///
+    /// * `max_recv` - Maximum amount of data received. `consts::DEFAULT_MAX_RECV_SIZE` is used by the other functions.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(cmd = ?&cmd[..cmd.len().min(3)], ?action, max_recv))
+    )]
+    pub async fn send_custom_read_command_with_max_recv(
+        &mut self,
+        cmd: &[i8],
+        action: StatusByteAction,
+        max_recv: usize,
+        timeout: std::time::Duration,
+    ) -> CamResult<Vec<u8>> {
+        self.send_read_command(cmd, action, max_recv, timeout, None)
+            .await
+    }
+
+    /// Internal function sending a read command, evaluating the status byte and retrying it if needed.
+    async fn send_read_command(
+        &mut self,
+        cmd: &[i8],
+        action: StatusByteAction,
+        max_recv: usize,
+        timeout: std::time::Duration,
+        retries: Option<u32>,
+    ) -> CamResult<Vec<u8>> {
+        let tries = 1 + retries.unwrap_or(self.default_tries);
+        let mut soft_retries = 0;
+        let mut power_save_reinits = 0;
+        let mut try_attempt = 0;
+
+        while try_attempt < tries {
+            let res = self.read_data(cmd, max_recv, timeout).await;
 
             if action == StatusByteAction::Ignore {
                 return res;
@@ -429,10 +1512,29 @@ impl HaCam {
                 received: 0,
             })?;
 
+            #[cfg(feature = "tracing")]
+            tracing::debug!(status = *status_byte, try_attempt, soft_retries, "Received status byte");
+
             match status_byte {
                 0 | 1 => return Ok(buf),
                 255 => {
                     warn!("Camera is in power save mode.");
+
+                    if let Some(on_power_save) = &self.on_power_save {
+                        on_power_save();
+                    }
+
+                    match self.max_power_save_reinits {
+                        Some(max_reinits) if power_save_reinits >= max_reinits => {
+                            error!("Camera is still in power save mode after {power_save_reinits} reinitializations");
+                            return Err(CamError::PowerSave {
+                                reinits: power_save_reinits,
+                            });
+                        }
+                        Some(_) => power_save_reinits += 1,
+                        None => try_attempt += 1,
+                    }
+
                     info!("Attempting to reinitialize the USB connection...");
                     self.initialize_comm().await?;
                     continue;
@@ -444,16 +1546,100 @@ impl HaCam {
                 ),
                 unknown => warn!("Other/unknown status code received {unknown}"),
             }
+
+            if try_attempt + 1 < tries {
+                runtime::sleep(self.retry_backoff(soft_retries)).await;
+                soft_retries += 1;
+            }
+
+            try_attempt += 1;
         }
 
-        error!("Exhausted retry attempts ({tries}) while sending command");
-        Err(CamError::SendCommand {
-            tries,
-            status_code: 0,
-        })
+        error!("Exhausted retry attempts ({tries}) while sending command");
+        Err(CamError::SendCommand {
+            tries,
+            status_code: 0,
+        })
+    }
+
+    /// Sends a raw read command to the camera and returns the received data (without the CSW).
+    ///
+    /// This is an advanced function meant for exploring undocumented commands. The status byte isn't evaluated
+    /// and the command isn't retried. Sending unknown commands may leave the camera in an unexpected state
+    /// (or even damage it), so use this at your own risk.
+    ///
+    /// * `cmd` - The command buffer. Usually 16 bytes.
+    /// * `max_recv` - Maximum amount of data received. Usually `consts::DEFAULT_MAX_RECV_SIZE`.
+    /// * `timeout` - Transfer timeout.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(cmd = ?&cmd[..cmd.len().min(3)], max_recv))
+    )]
+    pub async fn send_raw_read(
+        &mut self,
+        cmd: &[i8],
+        max_recv: usize,
+        timeout: std::time::Duration,
+    ) -> CamResult<Vec<u8>> {
+        self.read_data(cmd, max_recv, timeout).await
+    }
+
+    /// Sends a raw write command with the data buffer to the camera.
+    ///
+    /// This is an advanced function meant for exploring undocumented commands. The command isn't retried.
+    /// Sending unknown commands may leave the camera in an unexpected state (or even damage it),
+    /// so use this at your own risk.
+    ///
+    /// * `cmd` - The command buffer. Usually 16 bytes.
+    /// * `data` - Data buffer sent to the camera.
+    /// * `timeout` - Transfer timeout.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(cmd = ?&cmd[..cmd.len().min(3)], len = data.len()))
+    )]
+    pub async fn send_raw_write(
+        &mut self,
+        cmd: &[i8],
+        data: Vec<u8>,
+        timeout: std::time::Duration,
+    ) -> CamResult<()> {
+        self.write_data(cmd, data, timeout).await
+    }
+
+    /// Sends a status query command to the camera and returns the raw response.
+    /// Meant for experimenting with undocumented query commands.
+    ///
+    /// This is `send_custom_read_command` with the settings the known status queries use:
+    /// `StatusByteAction::IgnoreButRetryIfPowerSaving` and `consts::DEFAULT_TRANSFER_TIMEOUT`.
+    /// The status byte of an unknown command may just as well be part of its response,
+    /// so it's returned as is instead of being turned into an error, while a sleeping camera
+    /// is still woken up and asked again.
+    ///
+    /// The command querying the battery state isn't known, so there's no `query_battery_level`.
+    /// This is the way to look for it.
+    ///
+    /// * `cmd` - The command buffer (such as GET_CAMERA_STATUS). Usually 16 bytes.
+    ///
+    /// Returns the raw buffer returned by the camera.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(cmd = ?&cmd[..cmd.len().min(3)]))
+    )]
+    pub async fn send_status_query(&mut self, cmd: &[i8]) -> CamResult<Vec<u8>> {
+        self.send_custom_read_command(
+            cmd,
+            StatusByteAction::IgnoreButRetryIfPowerSaving,
+            consts::DEFAULT_TRANSFER_TIMEOUT,
+        )
+        .await
     }
 
     /// Gets the amount of remaining pictures to be read.
+    ///
+    /// The camera doesn't have any user-accessible storage - pictures only stay in its picture buffer
+    /// until they're transferred and videos are streamed to the host while recording. Thus there is
+    /// no storage/free space query, this count is the closest equivalent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn query_remaining_pic_num(&mut self) -> CamResult<u8> {
         let data = self
             .send_custom_read_command(
@@ -463,10 +1649,14 @@ impl HaCam {
             )
             .await?;
 
-        Ok(data[0])
+        data.first().copied().ok_or(CamError::InvalidLength {
+            expected: 1,
+            received: 0,
+        })
     }
 
     /// Clears the picture buffer of the camera.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn clear_camera_pic_buf(&mut self) -> CamResult<()> {
         self.send_custom_read_command(
             &consts::usb::CLEAR_PIC_BUF,
@@ -478,15 +1668,25 @@ impl HaCam {
         Ok(())
     }
 
-    /// Powers off the camera.
+    /// Powers off the camera. The command is soft retried only once.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn power_off(&mut self) -> CamResult<()> {
-        self.send_custom_read_command(
+        // Powering off stops the live view as well
+        self.pending_live_view_stop = false;
+
+        self.send_custom_read_command_with_retries(
             &consts::usb::POWER_OFF_CAMERA,
             StatusByteAction::Evaluate,
             consts::DEFAULT_TRANSFER_TIMEOUT,
+            Some(1),
         )
         .await?;
 
+        self.live_view_resolution = None;
+        self.live_view_initialized = false;
+        self.recording = false;
+        self.comm_initialized = false;
+
         Ok(())
     }
 
@@ -494,7 +1694,16 @@ impl HaCam {
     /// receive it with `get_live_view_frame` or stop it via the `stop_live_view` function.
     ///
     /// * `resolution` - Specifies the resolution, which is either high (1920x960) or low (1280x640)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(?resolution))
+    )]
     pub async fn start_live_view(&mut self, resolution: LiveViewResolution) -> CamResult<()> {
+        self.check_thermal_guard().await?;
+
+        // Offset 9 carries the raw enum value (10 for low, 9 for high), the same encoding
+        // as the resolution settings use. The command captured from the original app has 10
+        // (the default low resolution) at this offset, not the 0/1 index of the app's resolution picker.
         let mut cmd = consts::usb::START_LIVE_VIEW.to_vec();
         cmd[9] = resolution as i8;
 
@@ -505,12 +1714,20 @@ impl HaCam {
         )
         .await?;
 
+        self.live_view_resolution = Some(resolution);
+        self.live_view_initialized = true;
+        self.live_view_frame_count = 0;
+        self.last_live_view_frame_at = None;
+
         Ok(())
     }
 
     /// Stops the live view stream. The caller than then check the stop status
     /// via the `check_live_view_stop_request_status` function.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn stop_live_view(&mut self) -> CamResult<()> {
+        self.pending_live_view_stop = false;
+
         self.send_custom_read_command(
             &consts::usb::STOP_LIVE_VIEW,
             StatusByteAction::Evaluate,
@@ -518,10 +1735,144 @@ impl HaCam {
         )
         .await?;
 
+        self.live_view_resolution = None;
+
+        Ok(())
+    }
+
+    /// Starts the live view stream and polls `check_live_view_status` until the stream is running.
+    ///
+    /// * `resolution` - Specifies the resolution, which is either high (1920x960) or low (1280x640)
+    /// * `timeout` - If the stream doesn't start in time, `CamError::Timeout` is returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(?resolution)))]
+    pub async fn start_live_view_confirmed(
+        &mut self,
+        resolution: LiveViewResolution,
+        timeout: std::time::Duration,
+    ) -> CamResult<()> {
+        self.start_live_view(resolution).await?;
+
+        let deadline = runtime::Instant::now() + timeout;
+
+        while !self.check_live_view_status().await? {
+            if runtime::Instant::now() + consts::STATUS_POLL_INTERVAL > deadline {
+                error!("The live view didn't start in time");
+                return Err(CamError::Timeout {
+                    op: TransferOp::StatusPoll,
+                });
+            }
+
+            runtime::sleep(consts::STATUS_POLL_INTERVAL).await;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the live view stream (see `start_live_view`) and returns a handle to it,
+    /// which stops the live view when it's no longer needed (see `LiveViewHandle`).
+    ///
+    /// * `resolution` - Specifies the resolution, which is either high (1920x960) or low (1280x640)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(?resolution))
+    )]
+    pub async fn start_live_view_handle(
+        &mut self,
+        resolution: LiveViewResolution,
+    ) -> CamResult<LiveViewHandle<'_, T>> {
+        self.start_live_view(resolution).await?;
+
+        Ok(LiveViewHandle {
+            cam: self,
+            stopped: false,
+        })
+    }
+
+    /// Returns `true` if a `LiveViewHandle` was dropped without being stopped,
+    /// so the live view will be stopped before the next command.
+    pub fn has_pending_live_view_stop(&self) -> bool {
+        self.pending_live_view_stop
+    }
+
+    /// Stops the live view left running by a dropped `LiveViewHandle`, if any.
+    /// A failure is only logged, so it doesn't fail the command which follows.
+    async fn stop_pending_live_view(&mut self) {
+        if !self.pending_live_view_stop {
+            return;
+        }
+
+        info!("Stopping the live view left running by a dropped live view handle");
+
+        // Boxed, as stopping the live view sends a command, which calls this function again
+        if let Err(e) = Box::pin(self.stop_live_view()).await {
+            warn!("Couldn't stop the live view ({e})");
+        }
+    }
+
+    /// Stops the live view stream and polls `check_live_view_stop_request_status` until the stream is stopped.
+    ///
+    /// * `timeout` - If the stream doesn't stop in time, `CamError::Timeout` is returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn stop_live_view_confirmed(&mut self, timeout: std::time::Duration) -> CamResult<()> {
+        self.stop_live_view().await?;
+
+        let deadline = runtime::Instant::now() + timeout;
+
+        while !self.check_live_view_stop_request_status().await? {
+            if runtime::Instant::now() + consts::STATUS_POLL_INTERVAL > deadline {
+                error!("The live view didn't stop in time");
+                return Err(CamError::Timeout {
+                    op: TransferOp::StatusPoll,
+                });
+            }
+
+            runtime::sleep(consts::STATUS_POLL_INTERVAL).await;
+        }
+
         Ok(())
     }
 
+    /// Returns the CSW received at the end of the last command, which can be used to inspect
+    /// the status and residue reported by the camera.
+    pub fn last_csw(&self) -> Option<Csw> {
+        self.last_csw
+    }
+
+    /// Returns `true` if live view was started via `start_live_view` (and not stopped yet).
+    ///
+    /// This reflects the requested state tracked by this struct, the camera isn't queried.
+    pub fn is_live_view_active(&self) -> bool {
+        self.live_view_resolution.is_some()
+    }
+
+    /// Returns `true` if recording was started via `start_recording` and `check_start_recording_request`
+    /// confirmed it, until `check_stop_recording_request` confirms that it was stopped.
+    ///
+    /// This reflects the state tracked by this struct, the camera isn't queried.
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Returns a rough estimate of the captured picture size, based on the last known photo resolution
+    /// (the default resolution is assumed if it's unknown).
+    pub(crate) fn estimated_picture_size(&self) -> usize {
+        self.photo_resolution.unwrap_or_default().estimated_jpeg_bytes()
+    }
+
+    /// Returns `true` if live view was started at least once since the camera was opened
+    /// (or since it was reconnected or powered off), which is needed for capturing pictures which aren't all black.
+    pub fn has_initialized_live_view(&self) -> bool {
+        self.live_view_initialized
+    }
+
+    /// Returns the resolution passed to the last successful `start_live_view` call,
+    /// or `None` if live view isn't running (or was stopped via `stop_live_view`).
+    pub fn active_live_view_resolution(&self) -> Option<LiveViewResolution> {
+        self.live_view_resolution
+    }
+
     /// Checks the live view status. Returns `true` if the status is OK.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn check_live_view_status(&mut self) -> CamResult<bool> {
         let data = self
             .send_custom_read_command(
@@ -540,10 +1891,99 @@ impl HaCam {
     }
 
     /// Returns the live view frame and the camera's thermal status.
+    ///
+    /// The frame's `index` and `interval` are tracked from the last `start_live_view` call,
+    /// frames received via `get_live_view_frame_into` aren't counted.
+    ///
+    /// The frame is assembled in a buffer kept across the frames, and only its data is copied
+    /// into the returned frame, so every frame takes a single allocation of its own size.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn get_live_view_frame(&mut self) -> CamResult<(ThermalStatus, LiveViewFrame)> {
-        let mut buf: Vec<u8> = Vec::with_capacity(1048576);
+        self.receive_live_view_frame(None).await
+    }
+
+    /// Same as `get_live_view_frame`, but the total time spent assembling the frame (across all of its parts)
+    /// is limited by `frame_timeout`. If it's exceeded, `CamError::Timeout` is returned.
+    ///
+    /// The timeout is only checked between the parts: a part which is being received is limited
+    /// by the default transfer timeout on its own and is always received completely (along with its status),
+    /// so the frame may take up to one part longer than `frame_timeout`.
+    /// After a timeout, no response is left unread and the next command can be sent right away,
+    /// but the rest of the frame isn't requested, so the camera may return its remaining parts
+    /// for the next live view frame request.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn get_live_view_frame_with_timeout(
+        &mut self,
+        frame_timeout: std::time::Duration,
+    ) -> CamResult<(ThermalStatus, LiveViewFrame)> {
+        self.receive_live_view_frame(Some(frame_timeout)).await
+    }
+
+    async fn receive_live_view_frame(
+        &mut self,
+        frame_timeout: Option<std::time::Duration>,
+    ) -> CamResult<(ThermalStatus, LiveViewFrame)> {
+        // The frame is assembled in the kept buffer and only its data is copied out,
+        // so no allocation of the maximum frame size is made for every frame.
+        let mut buf = std::mem::take(&mut self.live_view_buf);
+
+        let start = runtime::Instant::now();
+        let deadline = frame_timeout.map(|frame_timeout| start + frame_timeout);
 
-        let start = tokio::time::Instant::now();
+        let res = self
+            .receive_live_view_frame_into(&mut buf, deadline)
+            .await
+            .map(|thermal_status| (thermal_status, buf.to_vec()));
+
+        self.live_view_buf = buf;
+
+        let (thermal_status, data) = res?;
+
+        let received_at = runtime::Instant::now();
+        let duration = received_at - start;
+
+        let interval = self
+            .last_live_view_frame_at
+            .replace(received_at)
+            .map(|last| received_at - last);
+
+        let index = self.live_view_frame_count;
+        self.live_view_frame_count += 1;
+
+        let frame = LiveViewFrame {
+            duration,
+            index,
+            interval,
+            // Converting into `Bytes` takes over the allocation, so no copy is made.
+            #[allow(clippy::useless_conversion)]
+            data: data.into(),
+        };
+
+        Ok((thermal_status, frame))
+    }
+
+    /// Receives the live view frame into the provided buffer and returns the camera's thermal status.
+    ///
+    /// The buffer is cleared first, but its allocation is kept, so the same buffer can be reused
+    /// for every frame instead of allocating a new one (as `get_live_view_frame` does for the frame it returns).
+    ///
+    /// Returns `CamError::InvalidLength` if a message is shorter than its header (32 bytes) or its declared length,
+    /// or if the whole frame is empty.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn get_live_view_frame_into(&mut self, buf: &mut Vec<u8>) -> CamResult<ThermalStatus> {
+        self.receive_live_view_frame_into(buf, None).await
+    }
+
+    /// Receives the frame parts into the buffer, returning `CamError::Timeout` once the deadline is reached.
+    ///
+    /// The deadline is only checked between the parts, as dropping a part mid-transfer would leave
+    /// its response unread for the next command.
+    async fn receive_live_view_frame_into(
+        &mut self,
+        buf: &mut Vec<u8>,
+        deadline: Option<runtime::Instant>,
+    ) -> CamResult<ThermalStatus> {
+        buf.clear();
 
         let thermal_status = loop {
             let data = self
@@ -572,34 +2012,45 @@ impl HaCam {
 
             buf.extend(&data[32..32 + rx_len]);
 
+            // The frame is either sent in a single message, or split into multiple ones,
+            // the last one possibly without any data.
             if data[1] == 1 {
                 // This message contains the last part of the frame.
                 break data[20];
             }
-        };
-
-        let duration = start.elapsed();
 
-        let frame = LiveViewFrame {
-            duration,
-            data: buf,
+            if deadline.is_some_and(|deadline| runtime::Instant::now() >= deadline) {
+                return Err(CamError::Timeout {
+                    op: TransferOp::LiveViewFrame,
+                });
+            }
         };
 
+        if buf.is_empty() {
+            warn!("Received an empty live view frame");
+            return Err(CamError::InvalidLength {
+                expected: 1,
+                received: 0,
+            });
+        }
+
         let thermal_status =
             ThermalStatus::try_from(thermal_status as i8).map_err(|_| CamError::InvalidFormat)?;
 
-        Ok((thermal_status, frame))
+        Ok(thermal_status)
     }
 
     /// Acquires the thumbnail after taking a picture (with the `take_picture` function).
     /// The `check_capture_status` function indicates, whether the thumbnail is ready to be received.
     ///
     /// Returns the raw thumbnail buffer.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn get_thumbnail(&mut self) -> CamResult<Vec<u8>> {
         let mut data = self
-            .send_custom_read_command(
+            .send_custom_read_command_with_max_recv(
                 &consts::usb::GET_PIC_THUMBNAIL,
                 StatusByteAction::Evaluate,
+                consts::THUMBNAIL_MAX_RECV_SIZE,
                 consts::DEFAULT_TRANSFER_TIMEOUT,
             )
             .await?;
@@ -631,6 +2082,12 @@ impl HaCam {
     /// * `received_pic_data_len` - Specifies the size of the part of the picture which was already received.
     ///
     /// Returns the picture buffer and a bool signifying if the partial buffer is the last.
+    /// Returns `CamError::InvalidFormat` if the declared length is larger than `consts::DEFAULT_MAX_RECV_SIZE`,
+    /// or if the buffer is empty without being the last.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(received_pic_data_len))
+    )]
     pub async fn get_partial_picture_buffer(
         &mut self,
         received_pic_data_len: u32,
@@ -660,6 +2117,17 @@ impl HaCam {
 
         let partial_pic_buf_len = u32::from_le_bytes(data[16..20].try_into().unwrap()) as usize;
 
+        // A part can't be larger than a whole response, and only the last part may be empty
+        // (an empty part which isn't the last would never finish the transfer).
+        if partial_pic_buf_len > consts::DEFAULT_MAX_RECV_SIZE
+            || (partial_pic_buf_len == 0 && !is_end)
+        {
+            error!(
+                "Invalid partial picture buffer length {partial_pic_buf_len} (is_end: {is_end})"
+            );
+            return Err(CamError::InvalidFormat);
+        }
+
         if data.len() < 20 + partial_pic_buf_len {
             return Err(CamError::InvalidLength {
                 expected: 20 + partial_pic_buf_len,
@@ -672,9 +2140,79 @@ impl HaCam {
         Ok((pic_buf, is_end))
     }
 
+    /// Asks the camera whether the picture was transferred completely, after the last partial picture buffer
+    /// was received (see `get_partial_picture_buffer`).
+    ///
+    /// Returns `true` if the camera confirmed the transfer (status byte 0).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn verify_picture_transfer(&mut self) -> CamResult<bool> {
+        let data = self
+            .send_custom_read_command(
+                &consts::usb::PIC_TRANSFER_STATUS_IS_OK,
+                StatusByteAction::IgnoreButRetryIfPowerSaving,
+                consts::DEFAULT_TRANSFER_TIMEOUT,
+            )
+            .await?;
+
+        let status = data.first().ok_or(CamError::InvalidLength {
+            expected: 1,
+            received: 0,
+        })?;
+
+        if *status != 0 {
+            warn!("Camera didn't confirm the picture transfer (status {status})");
+        }
+
+        Ok(*status == 0)
+    }
+
+    /// Transfers all of the pictures buffered in the camera (such as after a burst of `take_picture` calls),
+    /// until `query_remaining_pic_num` reports that none remain.
+    ///
+    /// The remaining picture count is queried again after every transferred picture,
+    /// so pictures taken while draining are transferred as well.
+    ///
+    /// * `on_picture` - Called with every transferred picture.
+    ///
+    /// Returns the amount of transferred pictures.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn drain_pictures(&mut self, mut on_picture: impl FnMut(Vec<u8>)) -> CamResult<usize> {
+        let mut drained = 0;
+
+        loop {
+            let remaining = self.query_remaining_pic_num().await?;
+
+            if remaining == 0 {
+                break;
+            }
+
+            debug!("Draining pictures, {remaining} remaining");
+
+            let mut picture = Vec::with_capacity(self.estimated_picture_size());
+
+            loop {
+                let (buf, is_end) = self.get_partial_picture_buffer(picture.len() as u32).await?;
+
+                picture.extend(buf);
+
+                if is_end {
+                    break;
+                }
+            }
+
+            on_picture(picture);
+            drained += 1;
+        }
+
+        Ok(drained)
+    }
+
     /// Starts the recording. The caller than then check the stop status
     /// via the `check_start_recording` function.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn start_recording(&mut self) -> CamResult<()> {
+        self.check_thermal_guard().await?;
+
         self.send_custom_read_command(
             &consts::usb::START_RECORDING,
             StatusByteAction::Evaluate,
@@ -687,6 +2225,7 @@ impl HaCam {
 
     /// Stops the recording. The caller than then check the stop status
     /// via the `check_stop_recording` function.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn stop_recording(&mut self) -> CamResult<()> {
         self.send_custom_read_command(
             &consts::usb::STOP_RECORDING,
@@ -702,6 +2241,7 @@ impl HaCam {
     ///
     /// Returns an enum with three possible states: whether the thumbnail is available, the caller should try again
     /// or if the picture is fully captured.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn check_capture_status(&mut self) -> CamResult<CaptureStatus> {
         let data = self
             .send_custom_read_command(
@@ -763,7 +2303,61 @@ impl HaCam {
         Ok(capture_status)
     }
 
+    /// Polls the capture status (via `check_capture_status`) until the camera reports something else than `TryAgain`,
+    /// which is either an available thumbnail or the fully captured picture.
+    ///
+    /// * `poll` - Interval between the status checks.
+    /// * `timeout` - Overall timeout. If the picture isn't ready in time, `CamError::Timeout` is returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn wait_for_capture(
+        &mut self,
+        poll: std::time::Duration,
+        timeout: std::time::Duration,
+    ) -> CamResult<CaptureStatus> {
+        let deadline = runtime::Instant::now() + timeout;
+
+        loop {
+            let status = self.check_capture_status().await?;
+
+            if !matches!(status, CaptureStatus::TryAgain) {
+                return Ok(status);
+            }
+
+            if runtime::Instant::now() + poll > deadline {
+                return Err(CamError::Timeout {
+                    op: TransferOp::StatusPoll,
+                });
+            }
+
+            runtime::sleep(poll).await;
+        }
+    }
+
+    /// Polls the capture status until the thumbnail is available, and then receives it.
+    /// Useful for showing a quick preview while the full picture is transferred afterwards.
+    ///
+    /// If the camera reports the picture as captured without reporting the thumbnail first,
+    /// the thumbnail is requested anyway.
+    ///
+    /// * `timeout` - Overall timeout. If the thumbnail isn't ready in time, `CamError::Timeout` is returned.
+    ///
+    /// Returns the raw thumbnail buffer and the capture status.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn await_thumbnail(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> CamResult<(Vec<u8>, CaptureStatus)> {
+        let status = self
+            .wait_for_capture(consts::STATUS_POLL_INTERVAL, timeout)
+            .await?;
+
+        let thumbnail = self.get_thumbnail().await?;
+
+        Ok((thumbnail, status))
+    }
+
     /// Checks the status of a live view stop request. Returns `true` if the status is OK.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn check_live_view_stop_request_status(&mut self) -> CamResult<bool> {
         let data = self
             .send_custom_read_command(
@@ -781,7 +2375,9 @@ impl HaCam {
         Ok(*status != 3 && *status != 1)
     }
 
-    /// Checks the status of a recording request. Returns `true` if the status is OK.
+    /// Checks the status of a recording request. Returns `true` if the status is OK,
+    /// in which case `is_recording` starts returning `true`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn check_start_recording_request(&mut self) -> CamResult<bool> {
         let data = self
             .send_custom_read_command(
@@ -796,10 +2392,18 @@ impl HaCam {
             received: 0,
         })?;
 
-        Ok(*status != 3 && *status != 1)
+        let confirmed = *status != 3 && *status != 1;
+
+        if confirmed {
+            self.recording = true;
+        }
+
+        Ok(confirmed)
     }
 
-    /// Checks the status of the request for stopping recording. Returns `true` if the status is OK.
+    /// Checks the status of the request for stopping recording. Returns `true` if the status is OK,
+    /// in which case `is_recording` starts returning `false`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn check_stop_recording_request(&mut self) -> CamResult<bool> {
         let data = self
             .send_custom_read_command(
@@ -814,7 +2418,13 @@ impl HaCam {
             received: 0,
         })?;
 
-        Ok(*status != 3 && *status != 1)
+        let confirmed = *status != 3 && *status != 1;
+
+        if confirmed {
+            self.recording = false;
+        }
+
+        Ok(confirmed)
     }
 
     /// Takes picture using the provided orientation. This function does not return the picture,
@@ -822,7 +2432,18 @@ impl HaCam {
     /// optionally acquire the thumbnail and then proceed to get the picture.
     ///
     /// * `orientation` - Specifies the orientation of the picture. (0/90/180/270 deg)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(?orientation))
+    )]
     pub async fn take_picture(&mut self, orientation: PictureOrientation) -> CamResult<()> {
+        if self.strict_live_view && !self.live_view_initialized {
+            error!("Refusing to capture, live view wasn't initialized");
+            return Err(CamError::LiveViewNotInitialized);
+        }
+
+        self.check_thermal_guard().await?;
+
         let mut cmd = consts::usb::TAKE_PICTURE.to_vec();
         cmd[8] = orientation as i8;
 
@@ -838,6 +2459,7 @@ impl HaCam {
 
     /// Gets the camera's execution status and thermal status. The purpose of the execution status
     /// data is unknown.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn get_camera_status(&mut self) -> CamResult<(u8, ThermalStatus)> {
         let data = self
             .send_custom_read_command(
@@ -864,7 +2486,37 @@ impl HaCam {
         Ok((execution_status, thermal_status))
     }
 
+    /// Returns the thermal status of the camera (via the `get_camera_status` function).
+    ///
+    /// This is a coarse state, the camera doesn't report its temperature in degrees (see `ThermalStatus`).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn query_thermal_status(&mut self) -> CamResult<ThermalStatus> {
+        let (_, thermal_status) = self.get_camera_status().await?;
+
+        Ok(thermal_status)
+    }
+
+    /// Returns the camera's status (thermal status, execution status, remaining picture count and firmware version)
+    /// in one struct.
+    ///
+    /// Note that this issues multiple commands (`get_camera_status`, `query_remaining_pic_num` and `get_camera_info`)
+    /// internally. An error of any of them is returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn status_snapshot(&mut self) -> CamResult<StatusSnapshot> {
+        let (execution_status, thermal_status) = self.get_camera_status().await?;
+        let remaining_pic_num = self.query_remaining_pic_num().await?;
+        let firmware_version = self.get_camera_info().await?;
+
+        Ok(StatusSnapshot {
+            execution_status,
+            thermal_status,
+            remaining_pic_num,
+            firmware_version,
+        })
+    }
+
     /// Returns the camera firmware version. The command returns more data, but its purpose is unknown.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn get_camera_info(&mut self) -> CamResult<Option<String>> {
         let data = self
             .send_custom_read_command(
@@ -874,9 +2526,9 @@ impl HaCam {
             )
             .await?;
 
-        if data.len() < 97 {
+        if data.len() < 98 {
             return Err(CamError::InvalidLength {
-                expected: 97,
+                expected: 98,
                 received: data.len(),
             });
         }
@@ -897,6 +2549,7 @@ impl HaCam {
     }
 
     /// Returns the "SCSI" version of the camera.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn get_scsi_version(&mut self) -> CamResult<Option<String>> {
         let data = self
             .send_custom_read_command(
@@ -906,10 +2559,10 @@ impl HaCam {
             )
             .await?;
 
-        if data.is_empty() {
+        if data.len() < 2 {
             return Err(CamError::InvalidLength {
-                expected: 1,
-                received: 0,
+                expected: 2,
+                received: data.len(),
             });
         }
 
@@ -928,10 +2581,23 @@ impl HaCam {
         Ok(None)
     }
 
+    /// Returns both the firmware version (see `get_camera_info`) and the "SCSI" version (see `get_scsi_version`).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn get_versions(&mut self) -> CamResult<Versions> {
+        Ok(Versions {
+            firmware: self.get_camera_info().await?,
+            scsi: self.get_scsi_version().await?,
+        })
+    }
+
     /// Writes one setting to the camera.
     /// 
     /// * `setting` - The type of setting.
     /// * `value` - The setting value (one signed byte)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(?setting, value))
+    )]
     pub async fn write_setting(&mut self, setting: SettingType, value: u8) -> CamResult<()> {
         let mut cmd = consts::usb::WRITE_GENERAL_SETTING.to_vec();
         cmd[2] = setting as i8;
@@ -943,6 +2609,10 @@ impl HaCam {
         )
         .await?;
 
+        if let SettingType::PhotoResolution = setting {
+            self.photo_resolution = PhotoResolution::try_from(value as i8).ok();
+        }
+
         Ok(())
     }
 
@@ -950,8 +2620,27 @@ impl HaCam {
     /// 
     /// * `setting` - The type of setting.
     /// 
-    /// Returns the value of the setting.
+    /// Returns the value of the setting, or `CamError::InvalidLength` if the camera responded with no data.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(?setting))
+    )]
     pub async fn read_setting(&mut self, setting: SettingType) -> CamResult<u8> {
+        // `read_setting_raw` rejects empty responses
+        Ok(self.read_setting_raw(setting).await?[0])
+    }
+
+    /// Same as `read_setting`, but returns the whole response buffer instead of its first byte.
+    /// Useful for reverse-engineering the undocumented settings.
+    /// 
+    /// * `setting` - The type of setting.
+    /// 
+    /// Returns `CamError::InvalidLength` if the camera responded with no data.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(?setting))
+    )]
+    pub async fn read_setting_raw(&mut self, setting: SettingType) -> CamResult<Vec<u8>> {
         let mut cmd = consts::usb::READ_GENERAL_SETTING.to_vec();
         cmd[2] = setting as i8;
 
@@ -963,10 +2652,19 @@ impl HaCam {
             )
             .await?;
 
-        Ok(data[0])
+        if data.is_empty() {
+            error!("Camera returned an empty response for the {setting:?} setting");
+            return Err(CamError::InvalidLength {
+                expected: 1,
+                received: 0,
+            });
+        }
+
+        Ok(data)
     }
 
     /// Returns all settings of the camera.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn read_all_settings(&mut self) -> CamResult<CamSettings> {
         let data = self
             .send_custom_read_command(
@@ -984,7 +2682,9 @@ impl HaCam {
         }
 
         let settings = CamSettings::from_bytes(&data)
-            .ok_or(CamError::InvalidFormat)?;
+            .inspect_err(|e| warn!("Unable to parse the camera settings ({e})"))?;
+
+        self.photo_resolution = Some(settings.photo_resolution);
 
         Ok(settings)
     }
@@ -992,6 +2692,7 @@ impl HaCam {
     /// Writes all settings (of the `CamSettings` struct) to the camera.
     /// 
     /// * `settings` - The camera settings.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub async fn write_all_settings(&mut self, settings: CamSettings) -> CamResult<()> {
         let data_bfr = settings.to_bytes();
 
@@ -1002,6 +2703,84 @@ impl HaCam {
         )
         .await?;
 
+        self.photo_resolution = Some(settings.photo_resolution);
+
+        Ok(())
+    }
+
+    /// Writes only the settings which differ between `current` and `target` (see `CamSettings::diff`),
+    /// each via the `write_setting` function.
+    ///
+    /// If the date-time differs, all settings are written at once instead (via `write_all_settings`),
+    /// as the date-time can't be written as an individual setting. The unknown bytes are then preserved
+    /// from `current`, unless `target` has its own raw buffer.
+    ///
+    /// * `current` - The settings currently stored in the camera.
+    /// * `target` - The desired settings.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn apply_settings_diff(
+        &mut self,
+        current: &CamSettings,
+        target: &CamSettings,
+    ) -> CamResult<()> {
+        if current.date_time != target.date_time {
+            return self.write_all_settings(target.with_raw_from(current)).await;
+        }
+
+        for (setting, value) in current.diff(target) {
+            self.write_setting(setting, value).await?;
+        }
+
         Ok(())
     }
 }
+
+/// Creates the 31-byte command header (CBW) sent before every command.
+///
+/// The header consists of the `USBC` magic, the check integer (big-endian), the maximum receiving size (little-endian),
+/// the transfer type (0x80 for read commands, 0 for write commands), the LUN (0), the command length (16)
+/// and the command buffer, padded with zeroes.
+///
+/// * `cmd` - Command buffer such as TAKE_PICTURE, GET_REMAINING_PIC_NUM, etc. Usually 16 bytes.
+/// * `max_recv_size` - Specifies the maximum receiving size (or the length of the written data for write commands).
+/// * `is_read` - Specifies if the command reads data from the camera or writes data to the camera.
+/// * `check_int` - Integer echoed back in the CSW (see `Csw::tag`).
+pub fn build_command_header(
+    cmd: &[i8],
+    max_recv_size: i32,
+    is_read: bool,
+    check_int: i32,
+) -> CamResult<Vec<u8>> {
+    <HaCam>::make_cmd_header(cmd, max_recv_size, is_read, check_int)
+}
+
+/// Extracts the status byte from the response to the keepalive command.
+///
+/// The camera responds either with a bare status byte (optionally followed by more data and the CSW),
+/// or with just the CSW - in that case, the CSW status is used.
+///
+/// Returns `CamError::InvalidLength` if the response is empty.
+fn parse_keepalive_status(buf: &[u8]) -> CamResult<u8> {
+    if buf.len() == Csw::LEN
+        && let Some(csw) = Csw::parse(buf)
+    {
+        return Ok(csw.status);
+    }
+
+    buf.first().copied().ok_or(CamError::InvalidLength {
+        expected: 1,
+        received: 0,
+    })
+}
+
+/// Awaits the future with the given timeout. If the timeout elapses,
+/// `CamError::Timeout` is returned with the provided operation.
+async fn with_timeout<F: std::future::Future>(
+    op: TransferOp,
+    timeout: std::time::Duration,
+    fut: F,
+) -> CamResult<F::Output> {
+    runtime::timeout(timeout, fut)
+        .await
+        .ok_or(CamError::Timeout { op })
+}
@@ -4,6 +4,7 @@ use std::time::Duration;
 pub const DEFAULT_TRANSFER_TIMEOUT: Duration = Duration::from_millis(2000);
 
 /// Timeout for sending firmware data.
+/// Not used yet, as the firmware update command hasn't been reverse-engineered.
 pub const FIRMWARE_TRANSFER_TIMEOUT: Duration = Duration::from_millis(5000);
 
 /// If too many keepalive commands fail in the given timeout, the connection is reinitialized.
@@ -15,10 +16,40 @@ pub const KEEPALIVE_INTERVAL: Duration = Duration::from_millis(500);
 /// Interval before attempting to reinitialize connection again due to a failure.
 pub const INIT_ATTEMPT_INTERVAL: Duration = Duration::from_millis(100);
 
+/// Initial delay before soft retrying a command. The delay is doubled after every soft retry.
+pub const DEFAULT_RETRY_BACKOFF_BASE: Duration = Duration::from_millis(50);
+
+/// Maximum delay before soft retrying a command.
+pub const DEFAULT_RETRY_BACKOFF_MAX: Duration = Duration::from_millis(800);
+
+/// Interval between status checks when waiting for the camera (for example for the capture status).
+pub const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Timeout for the live view to start or stop.
+pub const LIVE_VIEW_STATUS_TIMEOUT: Duration = Duration::from_millis(3000);
+
+/// Timeout for capturing a picture (until the picture is ready to be transferred).
+pub const CAPTURE_TIMEOUT: Duration = Duration::from_millis(30000);
+
+/// Default maximum amount of reconnection attempts (see `HaCam::reconnect`).
+pub const DEFAULT_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Default delay between reconnection attempts (see `HaCam::reconnect`).
+pub const DEFAULT_RECONNECT_BACKOFF: Duration = Duration::from_millis(1000);
+
+/// Maximum interval between the frames of a paced live view stream (see `HaCam::live_view_stream_paced`),
+/// used for frame rates whose interval would be longer.
+pub const MAX_FRAME_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Maximum amount of consecutive zero-length packets skipped while reading the response of a command.
+pub const MAX_ZERO_LENGTH_READS: u32 = 3;
+
 /// Receiving buffer size for the keepalive command.
 pub const KEEPALIVE_RX_BUF_SIZE: usize = 64;
 
 pub const DEFAULT_MAX_RECV_SIZE: usize = 65536;
+/// Maximum amount of data received for the picture thumbnail (see `HaCam::get_thumbnail`).
+pub const THUMBNAIL_MAX_RECV_SIZE: usize = 262144;
 pub const DEFAULT_CHUNK_SIZE: usize = 16384;
 
 pub const ENDPOINT_IN_ADDR: u8 = 0x82;
@@ -0,0 +1,106 @@
+use log::*;
+use openh264::formats::YUVSource as _;
+use yuv::{yuv420_to_rgb, YuvPlanarImage, YuvRange, YuvStandardMatrix};
+
+use crate::{cam::LiveViewFrame, CamResult};
+
+/// H.264 decoder used for decoding the live view frames.
+pub type H264Decoder = openh264::decoder::Decoder;
+
+/// Creates a new H.264 decoder for decoding the live view frames.
+pub fn new_decoder() -> CamResult<H264Decoder> {
+    Ok(H264Decoder::new()?)
+}
+
+/// Decoded live view frame with RGB8 pixels.
+#[derive(Debug, Clone)]
+pub struct RgbFrame {
+    pub width: u32,
+    pub height: u32,
+    /// Pixel data, row by row, 3 bytes (R, G, B) per pixel.
+    pub data: Vec<u8>,
+}
+
+impl LiveViewFrame {
+    /// Decodes the frame into RGB8 pixels.
+    ///
+    /// * `decoder` - The H.264 decoder. The frames depend on the previous ones,
+    ///   so the same decoder should be used for the whole live view stream.
+    ///
+    /// Returns `None` if the decoder didn't output a picture for this frame.
+    pub fn decode_rgb(&self, decoder: &mut H264Decoder) -> CamResult<Option<RgbFrame>> {
+        let mut rgb_frame = None;
+
+        for packet in openh264::nal_units(&self.data) {
+            match decoder.decode(packet) {
+                Ok(Some(decoded_yuv_frame)) => {
+                    let (w, h) = decoded_yuv_frame.dimensions();
+                    let (y, u, v) = decoded_yuv_frame.strides();
+
+                    let yuv_image = YuvPlanarImage {
+                        y_plane: decoded_yuv_frame.y(),
+                        u_plane: decoded_yuv_frame.u(),
+                        v_plane: decoded_yuv_frame.v(),
+                        y_stride: y as u32,
+                        u_stride: u as u32,
+                        v_stride: v as u32,
+                        width: w as u32,
+                        height: h as u32,
+                    };
+
+                    let column_cnt = 3 * w; // this is the stride - 3 represents the amount of color channels
+
+                    let mut rgb_image = vec![0; column_cnt * h];
+
+                    yuv420_to_rgb(
+                        &yuv_image,
+                        &mut rgb_image,
+                        column_cnt as u32,
+                        YuvRange::Full,
+                        YuvStandardMatrix::Bt601,
+                    )?;
+
+                    rgb_frame = Some(RgbFrame {
+                        width: w as u32,
+                        height: h as u32,
+                        data: rgb_image,
+                    });
+                }
+                Ok(None) => {}
+                // Single packets may fail to decode (for example before the first keyframe),
+                // which doesn't prevent the following packets from decoding.
+                Err(e) => debug!("Unable to decode H.264 packet ({e})"),
+            }
+        }
+
+        Ok(rgb_frame)
+    }
+}
+
+#[cfg(feature = "image")]
+impl RgbFrame {
+    /// Encodes the frame as a JPEG image.
+    ///
+    /// * `quality` - The JPEG quality, clamped to 1-100.
+    pub fn encode_jpeg(&self, quality: u8) -> CamResult<Vec<u8>> {
+        use image::{codecs::jpeg::JpegEncoder, ExtendedColorType, ImageEncoder as _};
+
+        let mut jpeg = Vec::new();
+
+        JpegEncoder::new_with_quality(&mut jpeg, quality.clamp(1, 100)).write_image(
+            &self.data,
+            self.width,
+            self.height,
+            ExtendedColorType::Rgb8,
+        )?;
+
+        Ok(jpeg)
+    }
+
+    /// Encodes the frame as a JPEG image (with quality 90) and saves it to the provided path.
+    pub fn save_jpeg(&self, path: impl AsRef<std::path::Path>) -> CamResult<()> {
+        std::fs::write(path, self.encode_jpeg(90)?)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{decode::RgbFrame, settings::PictureOrientation, CamError, CamResult};
+
+/// Rotates the equirectangular JPEG picture by the yaw specified by `orientation`, and re-encodes it.
+///
+/// Rotating a 360° picture around the vertical axis isn't a raster rotation - the equirectangular projection
+/// maps the yaw to the horizontal axis, so the rotation is a horizontal (wrapping) pixel shift.
+/// Every pixel column `x` is moved to `(x + width * yaw / 360) % width`.
+///
+/// `PictureOrientation::Deg0` returns the picture unchanged (without re-encoding it).
+pub fn rotate_equirectangular(jpeg: &[u8], orientation: PictureOrientation) -> CamResult<Vec<u8>> {
+    let yaw: u32 = match orientation {
+        PictureOrientation::Deg0 => return Ok(jpeg.to_vec()),
+        PictureOrientation::Deg90 => 90,
+        PictureOrientation::Deg180 => 180,
+        PictureOrientation::Deg270 => 270,
+    };
+
+    let mut image = image::load_from_memory_with_format(jpeg, image::ImageFormat::Jpeg)?.into_rgb8();
+
+    let (width, height) = image.dimensions();
+    let shift = (width as u64 * yaw as u64 / 360) as usize;
+
+    for row in image.chunks_exact_mut(width as usize * 3) {
+        row.rotate_right(shift * 3);
+    }
+
+    RgbFrame {
+        width,
+        height,
+        data: image.into_raw(),
+    }
+    .encode_jpeg(90)
+}
+
+/// Decodes the JPEG thumbnail (see `HaCam::get_thumbnail`).
+///
+/// Returns the width, height and the RGB8 pixels (row by row, 3 bytes per pixel),
+/// or `CamError::InvalidFormat` if the buffer is empty or not a valid JPEG.
+pub fn decode_thumbnail(jpeg: &[u8]) -> CamResult<(u32, u32, Vec<u8>)> {
+    let image = image::load_from_memory_with_format(jpeg, image::ImageFormat::Jpeg)
+        .map_err(|_| CamError::InvalidFormat)?
+        .into_rgb8();
+
+    let (width, height) = image.dimensions();
+
+    Ok((width, height, image.into_raw()))
+}
@@ -2,7 +2,9 @@
 //! 
 //! Tested on Windows and macOS. Should work on Linux as well. 
 //! This library uses the [nusb] library.
-//! The camera itself uses a standard LibUSB driver, so it works out of the box on macOS, but you need to select the driver manually on Windows (WinUSB).
+//! The timers use Tokio by default. To use the library with async-std or smol, disable the default features
+//! and enable the `async-std` feature (the timers then use `async-io`, and the blocking wrapper isn't available).
+//! The camera itself uses a standard LibUSB driver, so it works out of the box on macOS, but you need to select the driver manually on Windows (WinUSB). Otherwise, `HaCam::new` returns `CamError::DriverNotWinUsb`.
 //! 
 //! [nusb]: https://github.com/kevinmehall/nusb
 //! 
@@ -11,7 +13,7 @@
 //! More examples are provided in the `examples/` folder.
 //! 
 //! ```no_run
-//! use hacam_lib_rs::{cam::HaCam, settings::PictureOrientation, util::CamUtil};
+//! use hacam_lib_rs::prelude::*;
 //! 
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -39,9 +41,49 @@ pub mod util;
 /// Contains the main camera struct.
 pub mod cam;
 
+/// Contains the transport abstraction over the USB bulk transfers.
+pub mod transport;
+
+/// Contains a cloneable camera handle for sharing the camera between tasks.
+pub mod shared;
+
+/// Re-exports the commonly used items, so they can be imported with `use hacam_lib_rs::prelude::*;`.
+pub mod prelude;
+
+/// Contains the live view frame, picture transfer and hotplug event streams.
+mod stream;
+
+/// Contains the timers and file writes of the selected async runtime (`tokio` or `async-std` feature).
+mod runtime;
+
+/// Contains the H.264 decoding of live view frames.
+#[cfg(feature = "decode")]
+pub mod decode;
+
+/// Contains helpers for processing the captured pictures.
+#[cfg(feature = "image")]
+pub mod imaging;
+
+/// Contains the MJPEG streaming of the live view, for previews in a browser.
+#[cfg(feature = "mjpeg")]
+pub mod mjpeg;
+
+/// Contains the MP4 muxing of recorded frames.
+#[cfg(feature = "mux")]
+pub mod mux;
+
+/// Contains a blocking wrapper around the main camera struct.
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
 /// Crate-specific error enum. 
 /// Every function interacting with the camera returns a Result enum with this error type.
+///
+/// The enum is `#[non_exhaustive]`, as new variants are added while more of the camera's behavior is uncovered.
+/// Matches on it have to include a wildcard arm - `is_retryable` and `is_disconnect`
+/// classify the errors without matching on the individual variants.
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum CamError {
     #[error("Error while transfering USB data")]
     UsbTransfer(#[from] nusb::transfer::TransferError),
@@ -49,12 +91,20 @@ pub enum CamError {
     #[error("Internal I/O error occured")]
     Io(#[from] std::io::Error),
 
-    #[error("Timeout occured during I/O operation")]
-    Timeout(#[from] tokio::time::error::Elapsed),
+    /// The operation didn't complete in time.
+    ///
+    /// A `TransferOp::LiveViewFrame` timeout only happens between the parts of the frame,
+    /// so no response is left unread, but the rest of the frame may still be pending on the camera
+    /// (see `HaCam::get_live_view_frame_with_timeout`).
+    #[error("Timeout occured during I/O operation ({op})")]
+    Timeout { op: TransferOp },
 
     #[error("Invalid response format")]
     InvalidFormat,
 
+    #[error("Invalid settings format ({0})")]
+    SettingsFormat(#[from] settings::SettingsParseError),
+
     #[error("Invalid response length (expected: {expected}, received: {received})")]
     InvalidLength { expected: usize, received: usize },
 
@@ -70,8 +120,122 @@ pub enum CamError {
     #[error("Error while writing data")]
     Write,
 
+    #[error("Camera didn't transfer all of the data ({residue} bytes remaining)")]
+    PartialTransfer { residue: u32 },
+
+    #[cfg(feature = "decode")]
+    #[error("Error while decoding H.264 data")]
+    Decode(#[from] openh264::Error),
+
+    #[cfg(feature = "decode")]
+    #[error("Error while converting the decoded frame to RGB")]
+    YuvConversion(#[from] yuv::YuvError),
+
+    #[cfg(feature = "image")]
+    #[error("Error while encoding the image")]
+    Image(#[from] image::ImageError),
+
     #[error("Couldn't find a device with given VID/PID: {vid:#06X}:{pid:#06X}")]
     NoDeviceFound { vid: u16, pid: u16 },
+
+    #[error("Permission denied while opening the device {vid:#06X}:{pid:#06X}, {hint}")]
+    PermissionDenied { vid: u16, pid: u16, hint: String },
+
+    #[error(
+        "The device {vid:#06X}:{pid:#06X} isn't bound to the WinUSB driver, \
+         replace its driver with WinUSB (for example using Zadig: https://zadig.akeo.ie)"
+    )]
+    DriverNotWinUsb { vid: u16, pid: u16 },
+
+    #[error("Couldn't find the bulk IN/OUT endpoints on interface {interface}")]
+    EndpointNotFound { interface: u8 },
+
+    #[error("Camera didn't confirm the picture transfer, attempts: {tries}")]
+    PictureVerification { tries: u32 },
+
+    #[error("Received picture isn't a valid JPEG, attempts: {tries}")]
+    CorruptPicture { tries: u32 },
+
+    #[error("Live view wasn't initialized, so the picture would be all black (start the live view before capturing)")]
+    LiveViewNotInitialized,
+
+    #[error("Camera is stuck in power save mode ({reinits} reinitializations)")]
+    PowerSave { reinits: u32 },
+
+    #[error("Operation was cancelled")]
+    Cancelled,
+
+    #[error("Camera is overheated ({status})")]
+    Overheated { status: cam::ThermalStatus },
+}
+
+impl CamError {
+    /// Returns `true` if retrying the operation may succeed.
+    ///
+    /// * `Timeout`, `PartialTransfer` and the USB stalls/cancelled transfers are retryable.
+    /// * `SendCommand`, `ConnInit` and `Keepalive` (the camera asked to retry or reported an error) are retryable.
+    /// * `PowerSave` is retryable, the camera may wake up in the meantime (see `HaCam::wake`).
+    /// * Disconnects (see `is_disconnect`), `NoDeviceFound`, `InvalidFormat` and the other errors aren't retryable.
+    pub fn is_retryable(&self) -> bool {
+        use nusb::transfer::TransferError;
+
+        match self {
+            Self::Timeout { .. }
+            | Self::PartialTransfer { .. }
+            | Self::SendCommand { .. }
+            | Self::ConnInit { .. }
+            | Self::Keepalive { .. }
+            | Self::PowerSave { .. } => true,
+            Self::UsbTransfer(e) => matches!(e, TransferError::Stall | TransferError::Cancelled),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the error was caused by the camera being disconnected.
+    /// The camera handle is unusable afterwards, see `HaCam::reconnect`.
+    pub fn is_disconnect(&self) -> bool {
+        matches!(
+            self,
+            Self::UsbTransfer(nusb::transfer::TransferError::Disconnected)
+        )
+    }
+}
+
+/// Specifies which transfer operation an error (such as a timeout) occured in.
+///
+/// There's no firmware update routine yet, so firmware data written via `HaCam::send_raw_write`
+/// is reported as `DataWrite`. The enum is `#[non_exhaustive]`, so operations such as a dedicated
+/// firmware write can be added once they're supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransferOp {
+    /// Sending the command header to the camera.
+    CmdWrite,
+    /// Sending the data buffer of a write command (such as the settings).
+    DataWrite,
+    /// Receiving a chunk of data (or the status wrapper) from the camera.
+    ChunkRead,
+    /// Sending the keepalive command or receiving its response.
+    Keepalive,
+    /// Polling a status of the camera (such as the capture status) until it's ready.
+    StatusPoll,
+    /// Receiving all parts of a live view frame, the timeout is checked between the parts.
+    LiveViewFrame,
+}
+
+impl std::fmt::Display for TransferOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Self::CmdWrite => "command write",
+            Self::DataWrite => "data write",
+            Self::ChunkRead => "chunk read",
+            Self::Keepalive => "keepalive",
+            Self::StatusPoll => "status polling",
+            Self::LiveViewFrame => "live view frame assembly",
+        };
+
+        f.write_str(op)
+    }
 }
 
 type CamResult<T> = Result<T, CamError>;
\ No newline at end of file
@@ -0,0 +1,72 @@
+use futures::{Stream, StreamExt as _};
+
+use crate::{
+    cam::HaCam,
+    decode::new_decoder,
+    transport::Transport,
+    CamResult,
+};
+
+// A macro, so the boundary can be used in `concat!`.
+macro_rules! boundary {
+    () => {
+        "hacamframe"
+    };
+}
+
+/// The boundary separating the frames of the MJPEG stream.
+pub const BOUNDARY: &str = boundary!();
+
+/// The `Content-Type` header value of the MJPEG stream, to be sent in the HTTP response.
+pub const CONTENT_TYPE: &str = concat!("multipart/x-mixed-replace; boundary=", boundary!());
+
+/// Frames the JPEG image as a single part of the `multipart/x-mixed-replace` MJPEG stream
+/// (the boundary, the part headers and the image).
+pub fn part(jpeg: &[u8]) -> Vec<u8> {
+    let mut part = format!(
+        "--{BOUNDARY}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+        jpeg.len()
+    )
+    .into_bytes();
+
+    part.extend_from_slice(jpeg);
+    part.extend_from_slice(b"\r\n");
+
+    part
+}
+
+impl<T: Transport> HaCam<T> {
+    /// Returns a stream of the live view frames, decoded and encoded as JPEG images.
+    /// The images can be framed with `part` and served as an MJPEG stream,
+    /// or passed to any HTTP server (such as axum or hyper).
+    ///
+    /// The live view has to be started first (see `start_live_view`).
+    /// Frames which the decoder doesn't output a picture for are skipped.
+    /// The stream ends after yielding the first error of fetching a frame. Decoding and encoding errors
+    /// are yielded too, but the stream then continues with the next frame.
+    ///
+    /// * `target_fps` - The maximum frame rate, see `live_view_stream_paced`.
+    /// * `quality` - The JPEG quality, clamped to 1-100.
+    pub fn jpeg_stream(
+        &mut self,
+        target_fps: f32,
+        quality: u8,
+    ) -> CamResult<impl Stream<Item = CamResult<Vec<u8>>> + Send + '_> {
+        let mut decoder = new_decoder()?;
+
+        Ok(self
+            .live_view_stream_paced(target_fps)
+            .filter_map(move |res| {
+                let jpeg = res
+                    .and_then(|(_, frame)| frame.decode_rgb(&mut decoder))
+                    .and_then(|rgb_frame| {
+                        rgb_frame
+                            .map(|rgb_frame| rgb_frame.encode_jpeg(quality))
+                            .transpose()
+                    })
+                    .transpose();
+
+                futures::future::ready(jpeg)
+            }))
+    }
+}
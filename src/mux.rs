@@ -0,0 +1,167 @@
+use std::{
+    ffi::c_void,
+    io::{Seek, SeekFrom, Write},
+    os::raw::c_int,
+};
+
+use minimp4_sys::{
+    mp4_h26x_write_close, mp4_h26x_write_init, mp4_h26x_write_nal, mp4_h26x_writer_t, MP4E_close,
+    MP4E_mux_t, MP4E_open,
+};
+
+use crate::{
+    cam::{HaCam, LiveViewFrame},
+    settings::{Resolution as _, SettingType, VideoResolution},
+    transport::Transport,
+    CamError, CamResult,
+};
+
+/// Timescale of the video track, in ticks per second.
+const TIMESCALE: f64 = 90_000.0;
+
+/// Muxes the recorded H.264 frames into a MP4 file.
+///
+/// The duration of each sample is computed from the timing of its frame (see `LiveViewFrame::interval`),
+/// in ticks of the 90 kHz video timescale.
+///
+/// The muxer uses raw pointers internally, so the recorder isn't `Send`
+/// and has to stay on the task which receives the frames.
+pub struct Mp4Recorder<W: Write + Seek> {
+    mux: *mut MP4E_mux_t,
+    // The muxer keeps pointers to the writer and to the track state, so both have to stay at a fixed address.
+    h26x: Box<mp4_h26x_writer_t>,
+    _writer: Box<W>,
+    resolution: VideoResolution,
+    frame_count: u64,
+    closed: bool,
+}
+
+/// Write callback of the muxer, `token` points to the writer.
+unsafe extern "C" fn write_callback<W: Write + Seek>(
+    offset: i64,
+    buffer: *const c_void,
+    size: usize,
+    token: *mut c_void,
+) -> c_int {
+    // SAFETY: The token is the boxed writer of the recorder, which outlives the muxer,
+    // and the muxer passes a buffer of `size` bytes.
+    let (writer, buffer) = unsafe {
+        (
+            &mut *(token as *mut W),
+            std::slice::from_raw_parts(buffer as *const u8, size),
+        )
+    };
+
+    let res = writer
+        .seek(SeekFrom::Start(offset as u64))
+        .and_then(|_| writer.write_all(buffer));
+
+    res.is_err() as c_int
+}
+
+impl<W: Write + Seek> Mp4Recorder<W> {
+    /// Creates a new recorder writing into `writer`.
+    ///
+    /// * `resolution` - The video resolution the camera records in, used for the video track dimensions.
+    pub fn new(writer: W, resolution: VideoResolution) -> Self {
+        let mut writer = Box::new(writer);
+        // SAFETY: The track state only holds integers and pointers, for which zero is a valid value,
+        // and it's initialized by `mp4_h26x_write_init` before use.
+        let mut h26x: Box<mp4_h26x_writer_t> = Box::new(unsafe { std::mem::zeroed() });
+
+        // SAFETY: Both the writer and the track state are boxed, and are only dropped after the muxer is closed.
+        let mux = unsafe {
+            let mux = MP4E_open(
+                0,
+                0,
+                &mut *writer as *mut W as *mut c_void,
+                Some(write_callback::<W>),
+            );
+
+            mp4_h26x_write_init(
+                &mut *h26x,
+                mux,
+                resolution.w() as c_int,
+                resolution.h() as c_int,
+                0,
+            );
+
+            mux
+        };
+
+        Self {
+            mux,
+            h26x,
+            _writer: writer,
+            resolution,
+            frame_count: 0,
+            closed: false,
+        }
+    }
+
+    /// Creates a new recorder writing into `writer`, reading the video resolution from the camera's settings.
+    pub async fn for_cam<T: Transport>(cam: &mut HaCam<T>, writer: W) -> CamResult<Self> {
+        let resolution = cam.read_setting(SettingType::VideoResolution).await?;
+        let resolution =
+            VideoResolution::try_from(resolution as i8).map_err(|_| CamError::InvalidFormat)?;
+
+        Ok(Self::new(writer, resolution))
+    }
+
+    /// Returns the video resolution of the recording.
+    pub fn resolution(&self) -> VideoResolution {
+        self.resolution
+    }
+
+    /// Returns the amount of frames written so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Writes the frame into the MP4 file.
+    pub fn push_frame(&mut self, frame: &LiveViewFrame) {
+        // The first frame has no interval, so the time it took to fetch it is used instead.
+        let frame_time = frame.interval.unwrap_or(frame.duration).as_secs_f64();
+
+        // Every sample has to advance the timestamp, and the muxer stores the duration as an `int`.
+        let ticks = (frame_time * TIMESCALE).round().clamp(1.0, c_int::MAX as f64) as u32;
+
+        // SAFETY: The track state was initialized in `new`, and isn't closed yet.
+        let status = unsafe {
+            mp4_h26x_write_nal(
+                &mut *self.h26x,
+                frame.data.as_ptr(),
+                frame.data.len() as c_int,
+                ticks,
+            )
+        };
+
+        if status != 0 {
+            log::warn!("Failed to mux frame {} (status {status})", frame.index);
+        }
+
+        self.frame_count += 1;
+    }
+
+    /// Finalizes the MP4 file. This also happens when the recorder is dropped.
+    pub fn finish(mut self) {
+        self.close();
+    }
+
+    fn close(&mut self) {
+        if !self.closed {
+            // SAFETY: The muxer and the track state are closed exactly once, before the writer is dropped.
+            unsafe {
+                MP4E_close(self.mux);
+                mp4_h26x_write_close(&mut *self.h26x);
+            }
+            self.closed = true;
+        }
+    }
+}
+
+impl<W: Write + Seek> Drop for Mp4Recorder<W> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
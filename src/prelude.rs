@@ -0,0 +1,9 @@
+pub use crate::{
+    cam::{HaCam, HaCamConfig},
+    settings::{
+        CamSettings, LiveViewResolution, PhotoResolution, PictureOrientation, Resolution,
+        VideoResolution,
+    },
+    util::{CamUtil, CapturedPicture},
+    CamError,
+};
@@ -0,0 +1,55 @@
+use std::{future::Future, time::Duration};
+
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+compile_error!("Either the `tokio` or the `async-std` feature has to be enabled.");
+
+#[cfg(feature = "tokio")]
+pub(crate) use tokio::time::{sleep, sleep_until, Instant};
+
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) use std::time::Instant;
+
+/// Waits until the duration has elapsed.
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) async fn sleep(duration: Duration) {
+    async_io::Timer::after(duration).await;
+}
+
+/// Waits until the deadline is reached.
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) async fn sleep_until(deadline: Instant) {
+    async_io::Timer::at(deadline).await;
+}
+
+/// Awaits the future, returning `None` if it doesn't complete within the duration.
+pub(crate) async fn timeout<F: Future>(duration: Duration, fut: F) -> Option<F::Output> {
+    #[cfg(feature = "tokio")]
+    {
+        tokio::time::timeout(duration, fut).await.ok()
+    }
+
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    {
+        use futures::future::{select, Either};
+
+        match select(std::pin::pin!(fut), std::pin::pin!(sleep(duration))).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        }
+    }
+}
+
+/// Writes the data to a file.
+///
+/// Without Tokio, the file is written synchronously, which blocks the executor for the duration of the write.
+pub(crate) async fn write_file(path: impl AsRef<std::path::Path>, data: &[u8]) -> std::io::Result<()> {
+    #[cfg(feature = "tokio")]
+    {
+        tokio::fs::write(path, data).await
+    }
+
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    {
+        std::fs::write(path, data)
+    }
+}
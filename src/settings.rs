@@ -1,5 +1,7 @@
 use chrono::{Datelike as _, Timelike as _};
 
+use crate::{cam::HaCam, transport::Transport, CamResult};
+
 /// Trait implemented by all setting enums representing a 2D resolution.
 pub trait Resolution {
     /// Gets the width.
@@ -12,6 +14,9 @@ pub trait Resolution {
 #[repr(i8)]
 #[derive(Debug, Clone, Copy, Default, int_enum::IntEnum)]
 /// Specifies the resolution for the live view.
+///
+/// The raw values are sent to the camera (see `HaCam::start_live_view`),
+/// the commented indices are the ones used by the resolution picker of the original app.
 pub enum LiveViewResolution {
     #[default]
     /// 1280 x 640
@@ -20,6 +25,13 @@ pub enum LiveViewResolution {
     High = 9, // 1
 }
 
+impl LiveViewResolution {
+    /// Returns all of the values.
+    pub fn all() -> &'static [Self] {
+        &[Self::Low, Self::High]
+    }
+}
+
 impl Resolution for LiveViewResolution {
     fn w(&self) -> u32 {
         match self {
@@ -62,6 +74,20 @@ pub enum PhotoResolution {
     Low = 4, // 1
 }
 
+impl PhotoResolution {
+    /// Returns all of the values.
+    pub fn all() -> &'static [Self] {
+        &[Self::High, Self::Low]
+    }
+
+    /// Returns a rough estimate of the captured JPEG size in bytes, used for preallocating the picture buffer.
+    ///
+    /// Assumes about 4 bits per pixel, the actual size depends on the scene.
+    pub fn estimated_jpeg_bytes(&self) -> usize {
+        (self.w() * self.h()) as usize / 2
+    }
+}
+
 impl Resolution for PhotoResolution {
     fn w(&self) -> u32 {
         match self {
@@ -87,10 +113,24 @@ pub enum VideoResolution {
     /// 1280 x 640
     #[default]
     Low = 10, // 1
-    /// Undocumented.
+    /// Undocumented value, its actual resolution is unknown.
+    /// The `Resolution` implementation reports 1280 x 640 for it, which is only a guess
+    /// (`dimensions_for` returns `None` instead).
     Unknown = 11,
 }
 
+impl VideoResolution {
+    /// Returns all of the values, including the undocumented `Unknown` one (see `selectable`).
+    pub fn all() -> &'static [Self] {
+        &[Self::High, Self::Low, Self::Unknown]
+    }
+
+    /// Returns the values which can be offered to the user (without `Unknown`).
+    pub fn selectable() -> &'static [Self] {
+        &[Self::High, Self::Low]
+    }
+}
+
 impl Resolution for VideoResolution {
     fn w(&self) -> u32 {
         match self {
@@ -129,6 +169,27 @@ pub enum EvValue {
     Pos2,
 }
 
+impl EvValue {
+    /// Returns all of the values, ordered from the lowest to the highest compensation.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Neg2,
+            Self::Neg1_67,
+            Self::Neg1_33,
+            Self::Neg1,
+            Self::Neg0_67,
+            Self::Neg0_33,
+            Self::None,
+            Self::Pos0_33,
+            Self::Pos0_67,
+            Self::Pos1,
+            Self::Pos1_33,
+            Self::Pos1_67,
+            Self::Pos2,
+        ]
+    }
+}
+
 #[repr(i8)]
 #[derive(Debug, Clone, Copy, Default, int_enum::IntEnum)]
 /// Specifies the white balance as a preset.
@@ -141,6 +202,33 @@ pub enum WhiteBalance {
     Fluorescent = 4,
 }
 
+impl WhiteBalance {
+    /// Returns all of the values.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Auto,
+            Self::Sunny,
+            Self::Cloudy,
+            Self::Tungsten,
+            Self::Fluorescent,
+        ]
+    }
+
+    /// Returns the nominal color temperature of the preset in Kelvin, for display purposes.
+    ///
+    /// The camera doesn't support setting the white balance as a Kelvin value.
+    /// As `Auto` doesn't have a fixed color temperature, it's reported as neutral daylight (5500 K).
+    pub fn approx_kelvin(&self) -> u16 {
+        match self {
+            Self::Auto => 5500,
+            Self::Sunny => 5200,
+            Self::Cloudy => 6000,
+            Self::Tungsten => 3200,
+            Self::Fluorescent => 4000,
+        }
+    }
+}
+
 #[repr(i8)]
 #[derive(Debug, Clone, Copy, Default, int_enum::IntEnum)]
 /// Specifies the camera color filter.
@@ -157,6 +245,38 @@ pub enum FilterValue {
     Newspaper,
 }
 
+impl FilterValue {
+    /// Returns all of the filters, in the order shown by the camera app.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::None,
+            Self::Faded,
+            Self::Nimbus,
+            Self::Tea,
+            Self::Twilight,
+            Self::Sapphire,
+            Self::Vintage,
+            Self::Greyscale,
+            Self::Newspaper,
+        ]
+    }
+
+    /// Returns the user-facing name of the filter.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Faded => "Faded",
+            Self::Nimbus => "Nimbus",
+            Self::Tea => "Tea",
+            Self::Twilight => "Twilight",
+            Self::Sapphire => "Sapphire",
+            Self::Vintage => "Vintage",
+            Self::Greyscale => "Greyscale",
+            Self::Newspaper => "Newspaper",
+        }
+    }
+}
+
 #[repr(i8)]
 #[derive(Debug, Clone, Copy, Default, int_enum::IntEnum)]
 /// Specifies the logo type superimposed on the camera. (either the Huawei logo or no logo)
@@ -166,6 +286,13 @@ pub enum LogoType {
     None = 0,
 }
 
+impl LogoType {
+    /// Returns all of the values.
+    pub fn all() -> &'static [Self] {
+        &[Self::None, Self::HuaweiLogo]
+    }
+}
+
 #[repr(i8)]
 #[derive(Debug, Clone, Copy, Default, int_enum::IntEnum)]
 /// Specifies the bitrate. Usually a higher bitrate is set for higher quality video.
@@ -177,8 +304,47 @@ pub enum Bitrate {
     Bitrate2 = 16,
 }
 
+impl Bitrate {
+    /// Minimum bitrate (in Mbps) which is considered sufficient for `VideoResolution::High`.
+    const MIN_HIGH_RES_MBPS: u32 = 8;
+
+    /// Returns all of the values, including `Unset` (see `selectable`).
+    pub fn all() -> &'static [Self] {
+        &[Self::Unset, Self::Bitrate0, Self::Bitrate1, Self::Bitrate2]
+    }
+
+    /// Returns the values which can be offered to the user (without `Unset`).
+    pub fn selectable() -> &'static [Self] {
+        &[Self::Bitrate0, Self::Bitrate1, Self::Bitrate2]
+    }
+
+    /// Returns the encoder bitrate in Mbps, or `None` if the bitrate is unset (the camera picks it).
+    ///
+    /// The values are derived from the raw setting values (4, 8 and 16), they haven't been
+    /// confirmed against the firmware yet.
+    pub fn as_mbps(&self) -> Option<u32> {
+        match self {
+            Self::Unset => None,
+            Self::Bitrate0 => Some(4),
+            Self::Bitrate1 => Some(8),
+            Self::Bitrate2 => Some(16),
+        }
+    }
+
+    /// Returns `false` if the bitrate is too low for the video resolution, so UIs can warn about it.
+    ///
+    /// Only `VideoResolution::High` requires a higher bitrate (at least 8 Mbps).
+    /// An unset bitrate is always valid, as the camera picks it on its own.
+    pub fn is_valid_for(&self, res: VideoResolution) -> bool {
+        match (self.as_mbps(), res) {
+            (Some(mbps), VideoResolution::High) => mbps >= Self::MIN_HIGH_RES_MBPS,
+            _ => true,
+        }
+    }
+}
+
 #[repr(i8)]
-#[derive(Debug, Clone, Copy, int_enum::IntEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, int_enum::IntEnum)]
 /// Represents a specific setting type, such as a `PhotoResolution` setting.
 pub enum SettingType {
     PhotoResolution = 3,
@@ -191,6 +357,41 @@ pub enum SettingType {
     Bitrate = 12,
 }
 
+impl SettingType {
+    /// Returns the offset of the setting in the settings buffer (see `CamSettings::to_bytes`),
+    /// or `None` if the setting isn't a part of `CamSettings`.
+    fn buffer_offset(self) -> Option<usize> {
+        match self {
+            Self::PhotoResolution => Some(2),
+            Self::VideoResolution => Some(3),
+            Self::EvBalance => Some(6),
+            Self::WhiteBalance => Some(7),
+            Self::Filter => Some(32),
+            Self::Bitrate => Some(35),
+            Self::LogoType => Some(39),
+            Self::ShutterTime => None,
+        }
+    }
+}
+
+/// Returns the `(width, height)` of a resolution setting value, as read via `HaCam::read_setting`.
+///
+/// Returns `None` if the setting isn't a resolution or the value is unknown.
+pub fn dimensions_for(setting: SettingType, raw: u8) -> Option<(u32, u32)> {
+    fn dimensions(resolution: impl Resolution) -> (u32, u32) {
+        (resolution.w(), resolution.h())
+    }
+
+    match setting {
+        SettingType::PhotoResolution => PhotoResolution::try_from(raw as i8).ok().map(dimensions),
+        SettingType::VideoResolution => VideoResolution::try_from(raw as i8)
+            .ok()
+            .filter(|r| !matches!(r, VideoResolution::Unknown))
+            .map(dimensions),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct CamSettings {
     pub photo_resolution: PhotoResolution, // @2
@@ -201,19 +402,49 @@ pub struct CamSettings {
     pub filter: FilterValue,               // @32
     pub bitrate: Bitrate,                  // @35
     pub logo_type: LogoType,               // @39
+
+    /// The raw settings buffer this struct was parsed from, see `raw`.
+    raw: Option<Vec<u8>>,
 }
 
 impl CamSettings {
+    /// Returns the raw settings buffer this struct was parsed from, if any.
+    /// `to_bytes` writes the known fields on top of it, so the bytes which aren't decoded
+    /// are preserved when writing the settings back to the camera.
+    pub fn raw(&self) -> Option<&[u8]> {
+        self.raw.as_deref()
+    }
+
+    /// Returns a copy of the settings, taking the raw buffer from `other` if these settings don't have their own.
+    pub(crate) fn with_raw_from(&self, other: &CamSettings) -> Self {
+        Self {
+            raw: self.raw.clone().or_else(|| other.raw.clone()),
+            ..self.clone()
+        }
+    }
+
+    /// Serializes the settings into the buffer sent to the camera (48 bytes long).
+    ///
+    /// If the settings were parsed from a buffer (see `raw`), the unknown bytes are taken from it,
+    /// otherwise they're zeroed. A raw buffer longer than 48 bytes is kept whole.
+    ///
+    /// The year is stored as a little-endian `u16`, so years outside of `0..=65535`
+    /// are saturated to the nearest representable year instead of wrapping around.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bfr = vec![0; 48];
+        let mut bfr = self.raw.clone().unwrap_or_default();
+
+        if bfr.len() < 48 {
+            bfr.resize(48, 0);
+        }
 
         bfr[2] = self.photo_resolution as u8;
         bfr[3] = self.video_resolution as u8;
         bfr[6] = self.evb as u8;
         bfr[7] = self.wb as u8;
 
-        bfr[10] = self.date_time.year() as u8;
-        bfr[11] = (self.date_time.year() >> 8) as u8;
+        let year = self.date_time.year().clamp(0, u16::MAX as i32) as u16;
+
+        bfr[10..12].copy_from_slice(&year.to_le_bytes());
         bfr[12] = self.date_time.month() as u8;
         bfr[13] = self.date_time.day() as u8;
         bfr[14] = self.date_time.hour() as u8;
@@ -229,15 +460,39 @@ impl CamSettings {
         bfr
     }
 
-    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+    /// Same as `to_bytes`, but the millisecond field of the date and time is zeroed.
+    /// Useful for firmware which only tracks whole seconds.
+    pub fn to_bytes_no_millis(&self) -> Vec<u8> {
+        let mut bfr = self.to_bytes();
+
+        bfr[18] = 0;
+        bfr[19] = 0;
+
+        bfr
+    }
+
+    /// Parses the settings buffer received from the camera.
+    ///
+    /// Returns an error describing which field couldn't be parsed.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, SettingsParseError> {
         if data.len() < 40 {
-            return None;
+            return Err(SettingsParseError::TooShort {
+                expected: 40,
+                received: data.len(),
+            });
         }
 
-        let photo_resolution = PhotoResolution::try_from(data[2] as i8).ok()?;
-        let video_resolution = VideoResolution::try_from(data[3] as i8).ok()?;
-        let evb = EvValue::try_from(data[6] as i8).ok()?;
-        let wb = WhiteBalance::try_from(data[7] as i8).ok()?;
+        let photo_resolution = parse_field(data, "photo resolution", 2)?;
+        let video_resolution = parse_field(data, "video resolution", 3)?;
+
+        if matches!(video_resolution, VideoResolution::Unknown) {
+            log::warn!(
+                "The camera reported an undocumented video resolution ({}), its dimensions are unknown",
+                VideoResolution::Unknown as i8
+            );
+        }
+        let evb = parse_field(data, "EV balance", 6)?;
+        let wb = parse_field(data, "white balance", 7)?;
 
         let year = u16::from_le_bytes([data[10], data[11]]);
         let month = data[12];
@@ -247,20 +502,27 @@ impl CamSettings {
         let second = data[16];
         let ms = u16::from_le_bytes([data[18], data[19]]);
 
-        let date = chrono::NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32)?;
+        let date = chrono::NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32)
+            .ok_or(SettingsParseError::InvalidDate { year, month, day })?;
         let time = chrono::NaiveTime::from_hms_milli_opt(
             hour as u32,
             minute as u32,
             second as u32,
             ms as u32,
-        )?;
+        )
+        .ok_or(SettingsParseError::InvalidTime {
+            hour,
+            minute,
+            second,
+            ms,
+        })?;
         let date_time = chrono::NaiveDateTime::new(date, time);
 
-        let filter = FilterValue::try_from(data[32] as i8).ok()?;
-        let bitrate = Bitrate::try_from(data[35] as i8).ok()?;
-        let logo_type = LogoType::try_from(data[39] as i8).ok()?;
+        let filter = parse_field(data, "filter", 32)?;
+        let bitrate = parse_field(data, "bitrate", 35)?;
+        let logo_type = parse_field(data, "logo type", 39)?;
 
-        Some(Self {
+        Ok(Self {
             photo_resolution,
             video_resolution,
             evb,
@@ -269,6 +531,215 @@ impl CamSettings {
             filter,
             bitrate,
             logo_type,
+            raw: Some(data.to_vec()),
+        })
+    }
+
+    /// Same as `from_bytes`, but discards the error details.
+    pub fn from_bytes_opt(data: &[u8]) -> Option<Self> {
+        Self::from_bytes(data).ok()
+    }
+}
+
+impl CamSettings {
+    /// Returns a builder for the settings.
+    pub fn builder() -> CamSettingsBuilder {
+        CamSettingsBuilder::default()
+    }
+
+    /// Returns the individual setting writes (as used by `HaCam::write_setting`)
+    /// needed to transform these settings into `other`.
+    ///
+    /// The date-time isn't included, as it doesn't map to a single `SettingType`.
+    pub fn diff(&self, other: &CamSettings) -> Vec<(SettingType, u8)> {
+        self.setting_values()
+            .into_iter()
+            .zip(other.setting_values())
+            .filter(|((_, current), (_, target))| current != target)
+            .map(|(_, target)| target)
+            .collect()
+    }
+
+    /// Writes the settings to the camera one by one via `HaCam::write_setting`,
+    /// instead of a single `HaCam::write_all_settings`.
+    ///
+    /// Some firmware versions reject the bulk settings write while accepting the individual writes,
+    /// so this serves as a fallback. The date-time isn't written, as there's no per-field command for it.
+    ///
+    /// * `only` - If provided, only the listed settings are written, the rest are left untouched.
+    ///
+    /// Stops at the first failed write, the settings written before it stay applied.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn apply_individually<T: Transport>(
+        &self,
+        cam: &mut HaCam<T>,
+        only: Option<&[SettingType]>,
+    ) -> CamResult<()> {
+        for (setting, value) in self.setting_values() {
+            if only.is_some_and(|only| !only.contains(&setting)) {
+                continue;
+            }
+
+            cam.write_setting(setting, value).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the raw value (as used by `HaCam::read_setting` and `HaCam::write_setting`) of each setting,
+    /// for editors which handle the settings generically.
+    ///
+    /// The date-time isn't included, as it spans multiple bytes and doesn't map to a single `SettingType`.
+    pub fn as_setting_map(&self) -> Vec<(SettingType, u8)> {
+        self.setting_values().to_vec()
+    }
+
+    /// Creates the settings from the raw setting values, the reverse of `as_setting_map`.
+    ///
+    /// Settings missing from `map` are taken from `CamSettings::default()` (including the date-time),
+    /// settings which aren't a part of `CamSettings` (such as `SettingType::ShutterTime`) are ignored.
+    ///
+    /// Returns an error describing the first setting with an invalid value.
+    pub fn from_setting_map(
+        map: impl IntoIterator<Item = (SettingType, u8)>,
+    ) -> Result<Self, SettingsParseError> {
+        let mut bfr = Self::default().to_bytes();
+
+        for (setting, value) in map {
+            if let Some(offset) = setting.buffer_offset() {
+                bfr[offset] = value;
+            }
+        }
+
+        Ok(Self {
+            raw: None,
+            ..Self::from_bytes(&bfr)?
         })
     }
-}
\ No newline at end of file
+
+    /// Returns the raw value of each setting which can be written individually.
+    fn setting_values(&self) -> [(SettingType, u8); 7] {
+        [
+            (SettingType::PhotoResolution, self.photo_resolution as u8),
+            (SettingType::VideoResolution, self.video_resolution as u8),
+            (SettingType::EvBalance, self.evb as u8),
+            (SettingType::WhiteBalance, self.wb as u8),
+            (SettingType::Filter, self.filter as u8),
+            (SettingType::Bitrate, self.bitrate as u8),
+            (SettingType::LogoType, self.logo_type as u8),
+        ]
+    }
+}
+
+/// Builder for the `CamSettings` struct.
+///
+/// Unspecified fields are taken from `CamSettings::default()`, except for the date-time,
+/// which defaults to the current local time (so writing the settings doesn't reset the camera's clock).
+#[derive(Debug, Clone, Default)]
+pub struct CamSettingsBuilder {
+    settings: CamSettings,
+    date_time: Option<chrono::NaiveDateTime>,
+}
+
+impl CamSettingsBuilder {
+    /// Creates a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the picture resolution.
+    pub fn photo_resolution(mut self, photo_resolution: PhotoResolution) -> Self {
+        self.settings.photo_resolution = photo_resolution;
+        self
+    }
+
+    /// Sets the video resolution.
+    pub fn video_resolution(mut self, video_resolution: VideoResolution) -> Self {
+        self.settings.video_resolution = video_resolution;
+        self
+    }
+
+    /// Sets the exposure value compensation.
+    pub fn ev_value(mut self, evb: EvValue) -> Self {
+        self.settings.evb = evb;
+        self
+    }
+
+    /// Sets the white balance preset.
+    pub fn white_balance(mut self, wb: WhiteBalance) -> Self {
+        self.settings.wb = wb;
+        self
+    }
+
+    /// Sets the camera's date and time.
+    pub fn date_time(mut self, date_time: chrono::NaiveDateTime) -> Self {
+        self.date_time = Some(date_time);
+        self
+    }
+
+    /// Sets the color filter.
+    pub fn filter(mut self, filter: FilterValue) -> Self {
+        self.settings.filter = filter;
+        self
+    }
+
+    /// Sets the video bitrate.
+    pub fn bitrate(mut self, bitrate: Bitrate) -> Self {
+        self.settings.bitrate = bitrate;
+        self
+    }
+
+    /// Sets the superimposed logo type.
+    pub fn logo_type(mut self, logo_type: LogoType) -> Self {
+        self.settings.logo_type = logo_type;
+        self
+    }
+
+    /// Builds the settings. If no date-time was specified, the current local time is used.
+    pub fn build(self) -> CamSettings {
+        CamSettings {
+            date_time: self
+                .date_time
+                .unwrap_or_else(|| chrono::Local::now().naive_local()),
+            ..self.settings
+        }
+    }
+}
+
+/// Parses the setting enum at the given offset of the settings buffer.
+fn parse_field<T: TryFrom<i8>>(
+    data: &[u8],
+    field: &'static str,
+    offset: usize,
+) -> Result<T, SettingsParseError> {
+    T::try_from(data[offset] as i8).map_err(|_| SettingsParseError::InvalidValue {
+        field,
+        offset,
+        value: data[offset],
+    })
+}
+
+/// Error returned when the settings buffer received from the camera can't be parsed.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SettingsParseError {
+    #[error("Settings buffer is too short (expected: {expected}, received: {received})")]
+    TooShort { expected: usize, received: usize },
+
+    #[error("Invalid {field} value {value} at offset {offset}")]
+    InvalidValue {
+        field: &'static str,
+        offset: usize,
+        value: u8,
+    },
+
+    #[error("Invalid date (year: {year}, month: {month}, day: {day})")]
+    InvalidDate { year: u16, month: u8, day: u8 },
+
+    #[error("Invalid time (hour: {hour}, minute: {minute}, second: {second}, ms: {ms})")]
+    InvalidTime {
+        hour: u8,
+        minute: u8,
+        second: u8,
+        ms: u16,
+    },
+}
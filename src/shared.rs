@@ -0,0 +1,126 @@
+use std::{future::Future, sync::Arc};
+
+use futures::future::{select, Either};
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+use futures::lock::{Mutex, MutexGuard};
+#[cfg(feature = "tokio")]
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::{
+    cam::HaCam,
+    runtime,
+    transport::{NusbTransport, Transport},
+    CamResult,
+};
+
+/// Cloneable handle to a camera shared between multiple tasks (such as a keepalive task and a capture task).
+///
+/// The camera is held behind an `Arc<Mutex<HaCam>>`, so the commands (and their USB transfers)
+/// are serialized - a command always runs to completion before the next one starts.
+/// Use `lock` to run multiple commands without other tasks interleaving their commands in between.
+///
+/// With the `tokio` feature, the mutex is the fair `tokio::sync::Mutex`, so the tasks waiting for the camera
+/// (such as the keepalive task) get it in the order they asked for it. Without Tokio, the `futures` mutex
+/// is used instead, which doesn't guarantee fairness - a task locking the camera in a tight loop may starve the others.
+pub struct HaCamShared<T = NusbTransport> {
+    inner: Arc<Mutex<HaCam<T>>>,
+}
+
+// Derived `Clone` would require `T: Clone`, which isn't needed for cloning the `Arc`.
+impl<T> Clone for HaCamShared<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: Transport + 'static> HaCamShared<T> {
+    /// Wraps the camera for sharing.
+    pub fn new(cam: HaCam<T>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(cam)),
+        }
+    }
+
+    /// Locks the camera for exclusive access, waiting until other tasks release it.
+    pub async fn lock(&self) -> MutexGuard<'_, HaCam<T>> {
+        self.inner.lock().await
+    }
+
+    /// See `HaCam::send_keepalive`.
+    pub async fn send_keepalive(&self) -> CamResult<()> {
+        self.lock().await.send_keepalive().await
+    }
+
+    /// Spawns a Tokio task, which sends the keepalive command every `interval` (usually `consts::KEEPALIVE_INTERVAL`).
+    ///
+    /// Keepalive failures are only logged. The task runs until it's aborted via the returned handle.
+    /// With other runtimes, spawn a task calling `send_keepalive` in a loop instead.
+    #[cfg(feature = "tokio")]
+    pub fn spawn_keepalive(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let cam = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                if let Err(e) = cam.send_keepalive().await {
+                    log::warn!("Keepalive failed ({e})");
+                }
+            }
+        })
+    }
+
+    /// Same as `spawn_keepalive`, with the default keepalive interval.
+    #[cfg(feature = "tokio")]
+    pub fn spawn_default_keepalive(&self) -> tokio::task::JoinHandle<()> {
+        self.spawn_keepalive(crate::consts::KEEPALIVE_INTERVAL)
+    }
+
+    /// Awaits the future, while sending the keepalive command every `consts::KEEPALIVE_INTERVAL`
+    /// until it completes. Useful for long operations which don't talk to the camera all the time,
+    /// without spawning a keepalive task.
+    ///
+    /// The future may use the camera through this handle (or its clones). A keepalive is skipped
+    /// while the camera is locked, as the running command keeps the connection alive anyway,
+    /// and a keepalive that was started always finishes before the future is polled again.
+    /// Keepalive failures are only logged.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn with_keepalive<R>(&self, fut: impl Future<Output = CamResult<R>>) -> CamResult<R> {
+        let mut fut = std::pin::pin!(fut);
+
+        loop {
+            let tick = std::pin::pin!(runtime::sleep(crate::consts::KEEPALIVE_INTERVAL));
+
+            if let Either::Left((res, _)) = select(fut.as_mut(), tick).await {
+                return res;
+            }
+
+            #[cfg(feature = "tokio")]
+            let cam = self.inner.try_lock().ok();
+            #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+            let cam = self.inner.try_lock();
+
+            if let Some(mut cam) = cam
+                && let Err(e) = cam.send_keepalive().await
+            {
+                log::warn!("Keepalive failed ({e})");
+            }
+        }
+    }
+
+    /// Unwraps the camera, if this is the only remaining handle.
+    /// Otherwise the handle is returned back.
+    pub fn try_into_inner(self) -> Result<HaCam<T>, Self> {
+        Arc::try_unwrap(self.inner)
+            .map(Mutex::into_inner)
+            .map_err(|inner| Self { inner })
+    }
+}
+
+impl<T: Transport + 'static> From<HaCam<T>> for HaCamShared<T> {
+    fn from(cam: HaCam<T>) -> Self {
+        Self::new(cam)
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+
+use futures::{Stream, StreamExt as _};
+
+use crate::{
+    cam::{HaCam, HaCamConfig, HotplugEvent, LiveViewFrame, ThermalStatus},
+    consts,
+    runtime::{self, Instant},
+    transport::Transport,
+    CamError, CamResult,
+};
+
+impl HaCam {
+    /// Returns a stream of the connections and disconnections of the camera (with the default VID/PID),
+    /// so the camera can be opened as soon as it's plugged in, without polling.
+    ///
+    /// Cameras which are already connected when the stream is created aren't reported as connected,
+    /// but their disconnection is.
+    ///
+    /// Returns an error if hotplug events aren't supported on the platform.
+    pub fn watch_hotplug() -> CamResult<impl Stream<Item = HotplugEvent> + Send> {
+        let HaCamConfig { vid, pid, .. } = HaCamConfig::default();
+        let is_camera = move |d: &nusb::DeviceInfo| d.vendor_id() == vid && d.product_id() == pid;
+
+        // The watch is created first, so a camera connected in the meantime isn't missed.
+        let watch = nusb::watch_devices()?;
+
+        // Disconnection events only carry the device ID, so the IDs of the connected cameras are tracked.
+        let mut connected = nusb::list_devices()?
+            .filter(is_camera)
+            .map(|d| d.id())
+            .collect::<HashSet<_>>();
+
+        Ok(watch.filter_map(move |event| {
+            let event = match event {
+                nusb::hotplug::HotplugEvent::Connected(d) if is_camera(&d) => {
+                    connected.insert(d.id());
+                    Some(HotplugEvent::Connected(d))
+                }
+                nusb::hotplug::HotplugEvent::Disconnected(id) if connected.remove(&id) => {
+                    Some(HotplugEvent::Disconnected(id))
+                }
+                _ => None,
+            };
+
+            futures::future::ready(event)
+        }))
+    }
+}
+
+impl<T: Transport> HaCam<T> {
+    /// Returns a stream of live view frames, fetched one after another via `get_live_view_frame`.
+    ///
+    /// The live view has to be started first (see `start_live_view`).
+    /// The stream ends after yielding the first error.
+    pub fn live_view_stream(
+        &mut self,
+    ) -> impl Stream<Item = CamResult<(ThermalStatus, LiveViewFrame)>> + Send + '_ {
+        self.live_view_stream_paced(0.0)
+    }
+
+    /// Same as `live_view_stream`, but the frames are fetched at most `target_fps` times per second,
+    /// sleeping between the fetches as needed. Frames which arrive slower than the target
+    /// are passed through without any delay.
+    ///
+    /// * `target_fps` - The maximum frame rate. Values which aren't positive (or aren't finite) disable the pacing.
+    ///   The interval between the frames is capped at `consts::MAX_FRAME_INTERVAL`.
+    pub fn live_view_stream_paced(
+        &mut self,
+        target_fps: f32,
+    ) -> impl Stream<Item = CamResult<(ThermalStatus, LiveViewFrame)>> + Send + '_ {
+        let frame_interval = (target_fps.is_finite() && target_fps > 0.0).then(|| {
+            std::time::Duration::try_from_secs_f32(1.0 / target_fps)
+                .unwrap_or(consts::MAX_FRAME_INTERVAL)
+                .min(consts::MAX_FRAME_INTERVAL)
+        });
+
+        futures::stream::unfold(
+            (self, None::<Instant>, false),
+            move |(cam, last_fetch, errored)| async move {
+                if errored {
+                    return None;
+                }
+
+                if let (Some(interval), Some(last_fetch)) = (frame_interval, last_fetch) {
+                    runtime::sleep_until(last_fetch + interval).await;
+                }
+
+                let fetch_start = Instant::now();
+                let res = cam.get_live_view_frame().await;
+                let errored = res.is_err();
+
+                Some((res, (cam, Some(fetch_start), errored)))
+            },
+        )
+    }
+
+    /// Returns a stream of the partial picture buffers (see `get_partial_picture_buffer`), yielded as they arrive,
+    /// so the picture can be written to a file or a socket without buffering all of it.
+    ///
+    /// The picture has to be captured first (`check_capture_status` has to report `CaptureStatus::Captured`).
+    /// The stream ends after the last part of the picture, or after yielding the first error.
+    pub fn picture_stream(&mut self) -> impl Stream<Item = CamResult<Vec<u8>>> + Send + '_ {
+        futures::stream::unfold(
+            (self, 0u32, false),
+            |(cam, received_len, finished)| async move {
+                if finished {
+                    return None;
+                }
+
+                match cam.get_partial_picture_buffer(received_len).await {
+                    Ok((buf, is_end)) => {
+                        let received_len = received_len + buf.len() as u32;
+
+                        Some((Ok(buf), (cam, received_len, is_end)))
+                    }
+                    Err(e) => Some((Err(e), (cam, received_len, true))),
+                }
+            },
+        )
+    }
+
+    /// Returns a reader of the picture, backed by the partial picture buffers (see `picture_stream`),
+    /// for copying the picture via the async I/O traits (such as `tokio::io::copy`).
+    ///
+    /// The picture has to be captured first (`check_capture_status` has to report `CaptureStatus::Captured`).
+    pub fn picture_reader(&mut self) -> PictureReader<'_> {
+        PictureReader::new(self.picture_stream())
+    }
+}
+
+/// Reader of a captured picture, returned by `HaCam::picture_reader`.
+///
+/// Implements `futures::io::AsyncRead` and (with the `tokio` feature) `tokio::io::AsyncRead`,
+/// so the picture can be copied straight into a file or a hasher. The partial picture buffers
+/// are fetched as needed, the reader reaches the end after the last one.
+/// Errors are surfaced as `std::io::Error`s wrapping the `CamError`.
+pub struct PictureReader<'a> {
+    parts: std::pin::Pin<Box<dyn Stream<Item = CamResult<Vec<u8>>> + Send + 'a>>,
+    /// The current partial picture buffer
+    part: Vec<u8>,
+    /// Amount of bytes of `part` which were already read
+    pos: usize,
+}
+
+impl<'a> PictureReader<'a> {
+    pub(crate) fn new(parts: impl Stream<Item = CamResult<Vec<u8>>> + Send + 'a) -> Self {
+        Self {
+            parts: Box::pin(parts),
+            part: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Copies the next bytes of the picture into `dst`, fetching the next partial picture buffer if needed.
+    /// Returns 0 once the whole picture was read.
+    fn poll_read_into(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        dst: &mut [u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        use std::task::Poll;
+
+        while self.pos == self.part.len() {
+            match futures::ready!(self.parts.as_mut().poll_next(cx)) {
+                Some(Ok(part)) => {
+                    self.part = part;
+                    self.pos = 0;
+                }
+                Some(Err(CamError::Io(e))) => return Poll::Ready(Err(e)),
+                Some(Err(e)) => return Poll::Ready(Err(std::io::Error::other(e))),
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+
+        let len = dst.len().min(self.part.len() - self.pos);
+        dst[..len].copy_from_slice(&self.part[self.pos..self.pos + len]);
+        self.pos += len;
+
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl futures::io::AsyncRead for PictureReader<'_> {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        self.get_mut().poll_read_into(cx, buf)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for PictureReader<'_> {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let len = futures::ready!(self.get_mut().poll_read_into(cx, buf.initialize_unfilled()))?;
+        buf.advance(len);
+
+        std::task::Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,120 @@
+use std::{future::Future, time::Duration};
+
+use nusb::transfer::RequestBuffer;
+
+use crate::CamResult;
+
+/// Bulk transfers to and from the camera.
+///
+/// `HaCam` is generic over this trait, so it can be driven by something other than a real USB device
+/// (such as a mock returning scripted responses in tests). `NusbTransport` is the default implementation.
+pub trait Transport: Send {
+    /// Sends the data to the bulk OUT endpoint.
+    fn bulk_out(&mut self, data: Vec<u8>) -> impl Future<Output = CamResult<()>> + Send;
+
+    /// Receives up to `len` bytes from the bulk IN endpoint.
+    ///
+    /// `buf` is a previously received buffer (or an empty `Vec`), whose allocation may be reused.
+    fn bulk_in(
+        &mut self,
+        buf: Vec<u8>,
+        len: usize,
+    ) -> impl Future<Output = CamResult<Vec<u8>>> + Send;
+}
+
+/// Direction of a bulk transfer, as reported to a `TransferObserver`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    /// Data received from the camera.
+    In,
+    /// Data sent to the camera.
+    Out,
+}
+
+/// Observer of the bulk transfers made by `HaCam` (see `HaCam::set_transfer_observer`).
+///
+/// Only completed transfers are reported - failed or timed out transfers aren't.
+/// The observer is called inline after every transfer, so it should return quickly.
+/// It's also implemented for closures taking the same arguments as `on_transfer`.
+pub trait TransferObserver: Send + Sync {
+    /// Called after a bulk transfer completes.
+    ///
+    /// * `direction` - `In` for data received from the camera, `Out` for data sent to it.
+    /// * `bytes` - The amount of transferred bytes.
+    /// * `duration` - Time the transfer took.
+    fn on_transfer(&self, direction: TransferDirection, bytes: usize, duration: Duration);
+}
+
+impl<F: Fn(TransferDirection, usize, Duration) + Send + Sync> TransferObserver for F {
+    fn on_transfer(&self, direction: TransferDirection, bytes: usize, duration: Duration) {
+        self(direction, bytes, duration)
+    }
+}
+
+/// Transport over a claimed `nusb` interface.
+pub struct NusbTransport {
+    interface: nusb::Interface,
+    /// Bulk IN endpoint address (detected from the interface descriptor)
+    in_addr: u8,
+    /// Bulk OUT endpoint address (detected from the interface descriptor)
+    out_addr: u8,
+}
+
+impl NusbTransport {
+    /// Creates the transport over the interface, using the given bulk endpoint addresses.
+    pub fn new(interface: nusb::Interface, in_addr: u8, out_addr: u8) -> Self {
+        Self {
+            interface,
+            in_addr,
+            out_addr,
+        }
+    }
+
+    /// Returns the claimed interface.
+    pub fn interface(&self) -> &nusb::Interface {
+        &self.interface
+    }
+
+    /// Returns the address of the bulk IN endpoint in use.
+    pub fn in_endpoint(&self) -> u8 {
+        self.in_addr
+    }
+
+    /// Returns the address of the bulk OUT endpoint in use.
+    pub fn out_endpoint(&self) -> u8 {
+        self.out_addr
+    }
+}
+
+impl std::fmt::Debug for NusbTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NusbTransport")
+            .field("interface_number", &self.interface.interface_number())
+            .field("in_endpoint", &format_args!("{:#04X}", self.in_addr))
+            .field("out_endpoint", &format_args!("{:#04X}", self.out_addr))
+            .finish()
+    }
+}
+
+impl Transport for NusbTransport {
+    fn bulk_out(&mut self, data: Vec<u8>) -> impl Future<Output = CamResult<()>> + Send {
+        let transfer = self.interface.bulk_out(self.out_addr, data);
+
+        async move {
+            transfer.await.into_result()?;
+            Ok(())
+        }
+    }
+
+    fn bulk_in(
+        &mut self,
+        buf: Vec<u8>,
+        len: usize,
+    ) -> impl Future<Output = CamResult<Vec<u8>>> + Send {
+        let transfer = self
+            .interface
+            .bulk_in(self.in_addr, RequestBuffer::reuse(buf, len));
+
+        async move { Ok(transfer.await.into_result()?) }
+    }
+}
@@ -1,5 +1,34 @@
-use crate::{settings::{LiveViewResolution, PictureOrientation}, CamResult, cam::CaptureStatus, cam::HaCam};
-use std::future::Future;
+use crate::{consts, runtime::{self, Instant}, settings::{LiveViewResolution, PhotoResolution, PictureOrientation, SettingType}, transport::Transport, CamError, CamResult, TransferOp, cam::CaptureStatus, cam::HaCam};
+use log::*;
+use std::{future::Future, path::Path};
+
+/// Metadata of a captured picture, as reported by the camera's capture status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PictureMeta {
+    pub picture_status: u8,
+    pub picture_string: Option<String>,
+}
+
+/// Everything about a single captured picture.
+#[derive(Debug, Clone, Default)]
+pub struct CapturedPicture {
+    /// The JPG picture.
+    pub jpeg: Vec<u8>,
+    /// The raw thumbnail buffer, if the camera reported one.
+    pub thumbnail: Option<Vec<u8>>,
+    /// The amount of pictures stored in the camera, as reported with the thumbnail.
+    pub stored_pic_num: u8,
+    /// The picture metadata, if the camera reported it.
+    pub picture_meta: Option<PictureMeta>,
+}
+
+/// Checks whether the buffer looks like a complete JPEG, i.e. starts with the SOI marker (`FF D8`)
+/// and ends with the EOI marker (`FF D9`).
+///
+/// This is a lightweight check for truncated pictures or trailing garbage, the picture isn't decoded.
+pub fn looks_like_jpeg(data: &[u8]) -> bool {
+    data.len() >= 4 && data.starts_with(&[0xFF, 0xD8]) && data.ends_with(&[0xFF, 0xD9])
+}
 
 /// This trait provides convenience functions for the `HaCam` struct.
 pub trait CamUtil {
@@ -8,7 +37,10 @@ pub trait CamUtil {
     /// * `orientation` - Specifies the orientation of the picture. (0/90/180/270 deg)
     /// * `was_live_view_initialized` - If true, skips initialization of the camera's live view.
     ///   Live view needs to be initialized, otherwise the picture returned is all black.
-    /// * `on_thumbnail` - Optional closure which is called when a thumbnail is received. 
+    ///   If live view wasn't started since the camera was opened (see `HaCam::has_initialized_live_view`),
+    ///   it's initialized anyway, or `CamError::LiveViewNotInitialized` is returned
+    ///   if the strict live view check is enabled (see `HaCam::set_strict_live_view`).
+    /// * `on_thumbnail` - Optional closure which is called when a thumbnail is received.
     ///   (Rust complains if you just provide `None` as the parameter value, so provide `None::<fn(_)>` as a value)
     ///
     /// Returns the JPG picture as a byte buffer.
@@ -18,72 +50,386 @@ pub trait CamUtil {
         on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
         was_live_view_initialized: bool,
     ) -> impl Future<Output = CamResult<Vec<u8>>> + Send;
+
+    /// Same as `take_picture_and_get`, but returns the picture together with its thumbnail and metadata.
+    ///
+    /// * `orientation` - Specifies the orientation of the picture. (0/90/180/270 deg)
+    /// * `was_live_view_initialized` - If true, skips initialization of the camera's live view.
+    fn take_picture_full(
+        &mut self,
+        orientation: PictureOrientation,
+        was_live_view_initialized: bool,
+    ) -> impl Future<Output = CamResult<CapturedPicture>> + Send;
+
+    /// Convenience method for taking a picture and saving it (and optionally its thumbnail) to files.
+    /// Live view is initialized before taking the picture (see `take_picture_and_get`).
+    ///
+    /// * `orientation` - Specifies the orientation of the picture. (0/90/180/270 deg)
+    /// * `image_path` - Path of the JPG picture file.
+    /// * `thumbnail_path` - Optional path of the thumbnail file. (Provide `None::<&str>` if you don't need it)
+    ///
+    /// Returns the amount of bytes written to the picture file and to the thumbnail file
+    /// (`None` if no thumbnail was written).
+    fn capture_to_path(
+        &mut self,
+        orientation: PictureOrientation,
+        image_path: impl AsRef<Path> + Send,
+        thumbnail_path: Option<impl AsRef<Path> + Send>,
+    ) -> impl Future<Output = CamResult<(usize, Option<usize>)>> + Send;
+
+    /// Convenience method for taking a picture at the specified resolution.
+    /// Live view is initialized before taking the picture (see `take_picture_and_get`).
+    ///
+    /// * `orientation` - Specifies the orientation of the picture. (0/90/180/270 deg)
+    /// * `resolution` - The photo resolution, written to the camera before taking the picture.
+    /// * `restore` - If true, the previous photo resolution (read before writing the new one) is written back
+    ///   afterwards, even if taking the picture failed. Otherwise the new resolution persists.
+    ///
+    /// Returns the JPG picture as a byte buffer.
+    fn take_picture_at(
+        &mut self,
+        orientation: PictureOrientation,
+        resolution: PhotoResolution,
+        restore: bool,
+    ) -> impl Future<Output = CamResult<Vec<u8>>> + Send;
+
+    /// Same as `take_picture_and_get`, but also reports the transfer progress.
+    ///
+    /// * `on_progress` - Optional closure which is called after each partial picture buffer is received,
+    ///   with the amount of bytes received so far. The camera doesn't report the total picture size up front,
+    ///   so callers can only show an indeterminate progress (unless they also poll `query_remaining_pic_num`).
+    ///   (Provide `None::<fn(_)>` if you don't need it)
+    ///
+    /// Returns the JPG picture as a byte buffer.
+    fn take_picture_and_get_with_progress(
+        &mut self,
+        orientation: PictureOrientation,
+        on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
+        on_progress: Option<impl FnMut(usize) + Send>,
+        was_live_view_initialized: bool,
+    ) -> impl Future<Output = CamResult<Vec<u8>>> + Send;
+
+    /// Same as `take_picture_and_get`, but the operation can be aborted via the provided cancellation token.
+    /// The token is checked between the individual commands and between the partial picture buffers,
+    /// so an in-flight USB transfer is always finished first.
+    ///
+    /// If the token is triggered, `CamError::Cancelled` is returned. The picture may still be stored
+    /// in the camera's picture buffer, which is cleared by the next capture (or via `clear_camera_pic_buf`).
+    #[cfg(feature = "cancellation")]
+    fn take_picture_and_get_cancellable(
+        &mut self,
+        orientation: PictureOrientation,
+        on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
+        was_live_view_initialized: bool,
+        cancel: &tokio_util::sync::CancellationToken,
+    ) -> impl Future<Output = CamResult<Vec<u8>>> + Send;
 }
 
-impl CamUtil for HaCam {
+impl<T: Transport> CamUtil for HaCam<T> {
     async fn take_picture_and_get(
         &mut self,
         orientation: PictureOrientation,
-        mut on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
+        on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
         was_live_view_initialized: bool,
     ) -> CamResult<Vec<u8>> {
-        if !was_live_view_initialized {
-            self.start_live_view(LiveViewResolution::Low).await?;
+        capture_picture(
+            self,
+            orientation,
+            on_thumbnail,
+            None::<fn(_)>,
+            was_live_view_initialized,
+            false,
+            || false,
+        )
+        .await
+        .map(|picture| picture.jpeg)
+    }
 
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    async fn take_picture_full(
+        &mut self,
+        orientation: PictureOrientation,
+        was_live_view_initialized: bool,
+    ) -> CamResult<CapturedPicture> {
+        capture_picture(
+            self,
+            orientation,
+            None::<fn(_)>,
+            None::<fn(_)>,
+            was_live_view_initialized,
+            true,
+            || false,
+        )
+        .await
+    }
+
+    async fn capture_to_path(
+        &mut self,
+        orientation: PictureOrientation,
+        image_path: impl AsRef<Path> + Send,
+        thumbnail_path: Option<impl AsRef<Path> + Send>,
+    ) -> CamResult<(usize, Option<usize>)> {
+        let picture = capture_picture(
+            self,
+            orientation,
+            None::<fn(_)>,
+            None::<fn(_)>,
+            false,
+            thumbnail_path.is_some(),
+            || false,
+        )
+        .await?;
 
-            let live_view_start_status: bool = self.check_live_view_status().await?;
+        runtime::write_file(image_path, &picture.jpeg).await?;
 
-            if !live_view_start_status {
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let thumbnail_len = match (thumbnail_path, picture.thumbnail) {
+            (Some(thumbnail_path), Some(thumbnail)) => {
+                runtime::write_file(thumbnail_path, &thumbnail).await?;
+                Some(thumbnail.len())
             }
+            _ => None,
+        };
 
-            let _ = self.get_live_view_frame().await?;
+        Ok((picture.jpeg.len(), thumbnail_len))
+    }
 
-            self.stop_live_view().await?;
+    async fn take_picture_at(
+        &mut self,
+        orientation: PictureOrientation,
+        resolution: PhotoResolution,
+        restore: bool,
+    ) -> CamResult<Vec<u8>> {
+        let previous = if restore {
+            Some(self.read_setting(SettingType::PhotoResolution).await?)
+        } else {
+            None
+        };
+
+        self.write_setting(SettingType::PhotoResolution, resolution as u8)
+            .await?;
 
-            let live_view_stop_status: bool = self.check_live_view_stop_request_status().await?;
+        let res = capture_picture(
+            self,
+            orientation,
+            None::<fn(_)>,
+            None::<fn(_)>,
+            false,
+            false,
+            || false,
+        )
+        .await
+        .map(|picture| picture.jpeg);
 
-            if !live_view_stop_status {
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        if let Some(previous) = previous {
+            let restored = self
+                .write_setting(SettingType::PhotoResolution, previous)
+                .await;
+
+            if let Err(e) = &restored {
+                error!("Couldn't restore the previous photo resolution ({e})");
             }
+
+            // The capture error takes precedence over the restoring error
+            let jpeg = res?;
+            restored?;
+
+            return Ok(jpeg);
+        }
+
+        res
+    }
+
+    async fn take_picture_and_get_with_progress(
+        &mut self,
+        orientation: PictureOrientation,
+        on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
+        on_progress: Option<impl FnMut(usize) + Send>,
+        was_live_view_initialized: bool,
+    ) -> CamResult<Vec<u8>> {
+        capture_picture(
+            self,
+            orientation,
+            on_thumbnail,
+            on_progress,
+            was_live_view_initialized,
+            false,
+            || false,
+        )
+        .await
+        .map(|picture| picture.jpeg)
+    }
+
+    #[cfg(feature = "cancellation")]
+    async fn take_picture_and_get_cancellable(
+        &mut self,
+        orientation: PictureOrientation,
+        on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
+        was_live_view_initialized: bool,
+        cancel: &tokio_util::sync::CancellationToken,
+    ) -> CamResult<Vec<u8>> {
+        capture_picture(
+            self,
+            orientation,
+            on_thumbnail,
+            None::<fn(_)>,
+            was_live_view_initialized,
+            false,
+            || cancel.is_cancelled(),
+        )
+        .await
+        .map(|picture| picture.jpeg)
+    }
+}
+
+/// Shared implementation of the picture capture helpers.
+///
+/// * `on_thumbnail` - Called with the thumbnail.
+/// * `keep_thumbnail` - If true (and `on_thumbnail` is `None`), the thumbnail is kept in the returned `CapturedPicture`.
+/// * `on_progress` - Called with the amount of received bytes after each partial picture buffer.
+/// * `is_cancelled` - Checked between the individual commands, returns `CamError::Cancelled` if true.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(?orientation, was_live_view_initialized))
+)]
+async fn capture_picture<T: Transport>(
+    cam: &mut HaCam<T>,
+    orientation: PictureOrientation,
+    mut on_thumbnail: Option<impl FnMut(Vec<u8>) + Send>,
+    mut on_progress: Option<impl FnMut(usize) + Send>,
+    was_live_view_initialized: bool,
+    keep_thumbnail: bool,
+    is_cancelled: impl Fn() -> bool + Send,
+) -> CamResult<CapturedPicture> {
+    let check_cancelled = || {
+        if is_cancelled() {
+            Err(CamError::Cancelled)
+        } else {
+            Ok(())
         }
+    };
 
-        self.clear_camera_pic_buf().await?;
+    // The caller may be mistaken about live view being initialized, which would result in a black picture.
+    let needs_live_view = !was_live_view_initialized || !cam.has_initialized_live_view();
 
-        self.take_picture(orientation).await?;
+    if needs_live_view && was_live_view_initialized && cam.is_strict_live_view() {
+        error!("Refusing to capture, live view wasn't initialized");
+        return Err(CamError::LiveViewNotInitialized);
+    }
+
+    if needs_live_view {
+        cam.start_live_view_confirmed(LiveViewResolution::Low, consts::LIVE_VIEW_STATUS_TIMEOUT)
+            .await?;
+
+        let _ = cam.get_live_view_frame().await?;
+
+        cam.stop_live_view_confirmed(consts::LIVE_VIEW_STATUS_TIMEOUT)
+            .await?;
+    }
+
+    check_cancelled()?;
+
+    cam.clear_camera_pic_buf().await?;
 
-        loop {
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    cam.take_picture(orientation).await?;
 
-            let res = self.check_capture_status().await?;
+    let mut picture = CapturedPicture::default();
+    let mut thumbnail_received = false;
 
-            match res {
-                CaptureStatus::ThumbnailAvailable { .. } => {
+    let deadline = Instant::now() + consts::CAPTURE_TIMEOUT;
+
+    loop {
+        check_cancelled()?;
+
+        let res = cam.check_capture_status().await?;
+
+        match res {
+            CaptureStatus::ThumbnailAvailable {
+                stored_pic_num,
+                picture_status,
+                picture_string,
+                ..
+            } => {
+                picture.stored_pic_num = stored_pic_num;
+                picture.picture_meta = Some(PictureMeta {
+                    picture_status,
+                    picture_string,
+                });
+
+                // The camera keeps reporting the thumbnail until the picture is captured,
+                // so it's only transferred once.
+                if !thumbnail_received {
                     if let Some(ref mut on_thumbnail) = on_thumbnail {
-                        let thumbnail = self.get_thumbnail().await?;
+                        let thumbnail = cam.get_thumbnail().await?;
 
                         on_thumbnail(thumbnail);
+                        thumbnail_received = true;
+                    } else if keep_thumbnail {
+                        picture.thumbnail = Some(cam.get_thumbnail().await?);
+                        thumbnail_received = true;
                     }
+                }
+            },
+            CaptureStatus::TryAgain => {},
+            CaptureStatus::Captured => {
+                let tries = if cam.verifies_picture_transfers() || cam.validates_jpegs() {
+                    cam.default_tries().max(1)
+                } else {
+                    1
+                };
+                let mut corrupt = false;
 
-                    continue;
-                },
-                CaptureStatus::TryAgain => continue,
-                CaptureStatus::Captured => {
-                    let mut buf = Vec::new();
+                for try_no in 1..=tries {
+                    let mut buf = Vec::with_capacity(cam.estimated_picture_size());
                     loop {
+                        check_cancelled()?;
+
                         let (pbuf, is_end) =
-                            self.get_partial_picture_buffer(buf.len() as u32).await?;
+                            cam.get_partial_picture_buffer(buf.len() as u32).await?;
 
                         buf.extend(pbuf);
 
+                        if let Some(ref mut on_progress) = on_progress {
+                            on_progress(buf.len());
+                        }
+
                         if is_end {
                             break;
                         }
                     }
-                    return Ok(buf);
+
+                    if cam.verifies_picture_transfers() && !cam.verify_picture_transfer().await? {
+                        warn!("Picture transfer wasn't confirmed, reading the picture again ({try_no}/{tries})");
+                        corrupt = false;
+                        continue;
+                    }
+
+                    if cam.validates_jpegs() && !looks_like_jpeg(&buf) {
+                        warn!("Received picture isn't a valid JPEG, reading the picture again ({try_no}/{tries})");
+                        corrupt = true;
+                        continue;
+                    }
+
+                    picture.jpeg = buf;
+
+                    return Ok(picture);
                 }
+
+                return Err(if corrupt {
+                    error!("Couldn't receive a valid JPEG picture");
+                    CamError::CorruptPicture { tries }
+                } else {
+                    error!("The picture transfer couldn't be verified");
+                    CamError::PictureVerification { tries }
+                });
             }
         }
+
+        if Instant::now() + consts::STATUS_POLL_INTERVAL > deadline {
+            error!("The picture wasn't captured in time");
+            return Err(CamError::Timeout {
+                op: TransferOp::StatusPoll,
+            });
+        }
+
+        runtime::sleep(consts::STATUS_POLL_INTERVAL).await;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,152 @@
+//! Counts the allocations made while receiving the live view frames.
+
+mod common;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    future::Future,
+};
+
+use common::MockTransport;
+use futures::StreamExt as _;
+use hacam_lib_rs::cam::HaCam;
+
+/// Allocator counting the allocations (and their bytes) made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<Allocated> = const { Cell::new(Allocated { count: 0, bytes: 0 }) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Allocated {
+    count: usize,
+    bytes: usize,
+}
+
+fn count(size: usize) {
+    let _ = ALLOCATED.try_with(|allocated| {
+        let Allocated { count, bytes } = allocated.get();
+
+        allocated.set(Allocated {
+            count: count + 1,
+            bytes: bytes + size,
+        });
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the allocations made while awaiting the future.
+async fn allocations<F: Future>(fut: F) -> Allocated {
+    let before = ALLOCATED.with(Cell::get);
+    fut.await;
+    let after = ALLOCATED.with(Cell::get);
+
+    Allocated {
+        count: after.count - before.count,
+        bytes: after.bytes - before.bytes,
+    }
+}
+
+const PART_LEN: usize = 8192;
+const PARTS: usize = 3;
+
+/// Returns a camera with `frames` scripted live view frames, each split into `PARTS` parts.
+fn cam_with_frames(frames: usize) -> HaCam<MockTransport> {
+    let mut transport = MockTransport::new();
+
+    for _ in 0..frames {
+        for part in 0..PARTS {
+            let mut buf = vec![0; 32];
+            buf[1] = (part == PARTS - 1) as u8;
+            buf[28..32].copy_from_slice(&(PART_LEN as u32).to_le_bytes());
+            buf.resize(32 + PART_LEN, 0xAB);
+
+            transport.reply(buf);
+        }
+    }
+
+    // Keeps the recorded commands from reallocating mid-frame.
+    transport.sent.reserve(frames * PARTS * 2);
+
+    HaCam::from_transport(transport)
+}
+
+#[tokio::test]
+async fn reused_frame_buffer_isnt_reallocated() {
+    let mut cam = cam_with_frames(3);
+    let mut buf = Vec::new();
+
+    cam.get_live_view_frame_into(&mut buf).await.unwrap();
+
+    let second = allocations(cam.get_live_view_frame_into(&mut buf)).await;
+    let third = allocations(cam.get_live_view_frame_into(&mut buf)).await;
+
+    assert_eq!(buf.len(), PARTS * PART_LEN);
+    // Only the transfers allocate, the frame buffer keeps its allocation.
+    assert_eq!(second, third);
+}
+
+#[tokio::test]
+async fn returned_frames_take_a_single_allocation_of_their_size() {
+    let mut cam = cam_with_frames(6);
+    let mut buf = Vec::new();
+
+    cam.get_live_view_frame().await.unwrap();
+    cam.get_live_view_frame_into(&mut buf).await.unwrap();
+
+    let into = allocations(cam.get_live_view_frame_into(&mut buf)).await;
+    let frame = allocations(cam.get_live_view_frame()).await;
+
+    let expected = Allocated {
+        count: into.count + 1,
+        bytes: into.bytes + PARTS * PART_LEN,
+    };
+
+    assert_eq!(frame, expected);
+
+    // The stream receives its frames the same way.
+    let mut stream = std::pin::pin!(cam.live_view_stream());
+    stream.next().await.unwrap().unwrap();
+
+    assert_eq!(allocations(stream.next()).await, expected);
+}
+
+#[tokio::test]
+async fn chunked_reads_reuse_the_chunk_buffer() {
+    let mut transport = MockTransport::new();
+
+    // A single part spanning three 16 KiB chunks, followed by the CSW.
+    let mut part = vec![0; 32];
+    part[1] = 1;
+    part[28..32].copy_from_slice(&40000u32.to_le_bytes());
+    part.resize(32 + 40000, 0xAB);
+    transport.reply(part);
+
+    let mut cam = HaCam::from_transport(transport);
+    let mut buf = Vec::new();
+
+    cam.get_live_view_frame_into(&mut buf).await.unwrap();
+
+    // Every read after the first one gets the buffer of the previous chunk back.
+    assert_eq!(cam.transport().reused_bufs, 3);
+}
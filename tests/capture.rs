@@ -0,0 +1,435 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use common::{MockTransport, Reply};
+use hacam_lib_rs::{
+    cam::HaCam,
+    consts,
+    settings::{LiveViewResolution, PhotoResolution, PictureOrientation},
+    util::{looks_like_jpeg, CamUtil as _},
+    CamError,
+};
+
+/// Builds a response with the 20-byte header (`is_end` at offset 1, the length at offset 16) followed by the data.
+fn framed(is_end: bool, data: &[u8]) -> Vec<u8> {
+    let mut buf = vec![0; 20];
+    buf[1] = is_end as u8;
+    buf[16..20].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+    buf
+}
+
+fn thumbnail_available() -> Vec<u8> {
+    vec![0, 0, 0, 1, 0, 0, 0, 0, 0]
+}
+
+#[tokio::test(start_paused = true)]
+async fn take_picture_and_get_flow() {
+    let mut transport = MockTransport::new();
+    transport
+        .reply([0]) // start_live_view
+        .reply([0]) // clear_camera_pic_buf
+        .reply([0]) // take_picture
+        .reply([1])
+        .reply([1])
+        .reply(thumbnail_available())
+        .reply(framed(false, b"thumb"))
+        .reply([3])
+        .reply(framed(false, b"\xFF\xD8first"))
+        .reply(framed(true, b"second\xFF\xD9"));
+
+    let mut cam = HaCam::from_transport(transport);
+    cam.start_live_view(LiveViewResolution::Low).await.unwrap();
+
+    let thumbnails = Arc::new(AtomicUsize::new(0));
+    let on_thumbnail = {
+        let thumbnails = Arc::clone(&thumbnails);
+        move |thumbnail: Vec<u8>| {
+            assert_eq!(thumbnail, b"thumb");
+            thumbnails.fetch_add(1, Ordering::SeqCst);
+        }
+    };
+
+    let jpeg = cam
+        .take_picture_and_get(PictureOrientation::Deg0, Some(on_thumbnail), true)
+        .await
+        .unwrap();
+
+    assert_eq!(jpeg, b"\xFF\xD8firstsecond\xFF\xD9");
+    assert_eq!(thumbnails.load(Ordering::SeqCst), 1);
+    assert!(cam.transport().is_exhausted());
+
+    // The second partial buffer request carries the already received length.
+    let last_cmd = *cam.transport().commands().last().unwrap();
+    assert_eq!(&last_cmd[..3], &[122, 5, 2]);
+    assert_eq!(&last_cmd[8..12], &7u32.to_le_bytes());
+}
+
+#[tokio::test(start_paused = true)]
+async fn power_save_reinitializes_connection() {
+    let mut transport = MockTransport::new();
+    transport
+        .reply([255])
+        .push(Reply::Data(vec![0])) // OPEN_CONN response
+        .reply([4]);
+
+    let mut cam = HaCam::from_transport(transport);
+
+    let power_saves = Arc::new(AtomicUsize::new(0));
+    cam.set_on_power_save(Some({
+        let power_saves = Arc::clone(&power_saves);
+        move || {
+            power_saves.fetch_add(1, Ordering::SeqCst);
+        }
+    }));
+
+    assert_eq!(cam.query_remaining_pic_num().await.unwrap(), 4);
+    assert_eq!(power_saves.load(Ordering::SeqCst), 1);
+
+    let commands = cam.transport().commands();
+    assert_eq!(commands.len(), 3);
+    assert_eq!(commands[1], consts::scsi::OPEN_CONN_COMMAND.map(|b| b as u8));
+}
+
+#[tokio::test(start_paused = true)]
+async fn power_save_reinit_budget() {
+    let mut transport = MockTransport::new();
+    transport
+        .reply([255])
+        .push(Reply::Data(vec![0])) // OPEN_CONN response
+        .reply([255])
+        .push(Reply::Data(vec![0]))
+        .reply([255])
+        .push(Reply::Data(vec![0]))
+        .reply([255])
+        .reply([255]);
+
+    let mut cam = HaCam::from_transport(transport);
+
+    // Without a budget, the reinitializations use up the tries of the command.
+    assert!(matches!(
+        cam.power_off().await,
+        Err(CamError::SendCommand { tries: 2, .. })
+    ));
+
+    cam.set_max_power_save_reinits(Some(1));
+
+    assert!(matches!(
+        cam.query_remaining_pic_num().await,
+        Err(CamError::PowerSave { reinits: 1 })
+    ));
+
+    cam.set_max_power_save_reinits(Some(0));
+
+    assert!(matches!(
+        cam.query_remaining_pic_num().await,
+        Err(CamError::PowerSave { reinits: 0 })
+    ));
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test(start_paused = true)]
+async fn soft_retry_resends_command() {
+    let mut transport = MockTransport::new();
+    transport.reply([3]).reply([3]).reply([0]);
+
+    let mut cam = HaCam::from_transport(transport);
+
+    cam.clear_camera_pic_buf().await.unwrap();
+
+    let commands = cam.transport().commands();
+    assert_eq!(commands.len(), 3);
+    assert!(commands.iter().all(|cmd| *cmd == consts::usb::CLEAR_PIC_BUF.map(|b| b as u8)));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn soft_retries_back_off_exponentially() {
+    use std::time::Duration;
+
+    use hacam_lib_rs::{cam::StatusByteAction, transport::TransferDirection};
+
+    let mut transport = MockTransport::new();
+    transport.reply([3]).reply([3]).reply([3]).reply([3]).reply([0]);
+
+    let mut cam = HaCam::from_transport(transport);
+    cam.set_retry_backoff(Duration::from_millis(100), Duration::from_millis(300));
+
+    let sent_at = Arc::new(std::sync::Mutex::new(Vec::new()));
+    cam.set_transfer_observer(Some({
+        let sent_at = Arc::clone(&sent_at);
+        move |direction, _, _| {
+            if direction == TransferDirection::Out {
+                sent_at.lock().unwrap().push(tokio::time::Instant::now());
+            }
+        }
+    }));
+
+    cam.send_custom_read_command_with_retries(
+        &consts::usb::CLEAR_PIC_BUF,
+        StatusByteAction::Evaluate,
+        consts::DEFAULT_TRANSFER_TIMEOUT,
+        Some(4),
+    )
+    .await
+    .unwrap();
+
+    let gaps = sent_at
+        .lock()
+        .unwrap()
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .collect::<Vec<_>>();
+    assert_eq!(gaps, [100, 200, 300, 300].map(Duration::from_millis));
+}
+
+#[tokio::test]
+async fn drain_pictures_until_none_remain() {
+    let mut transport = MockTransport::new();
+    transport
+        .reply([1])
+        .reply(framed(true, b"first"))
+        // A picture was taken while draining.
+        .reply([1])
+        .reply(framed(false, b"sec"))
+        .reply(framed(true, b"ond"))
+        .reply([0]);
+
+    let mut cam = HaCam::from_transport(transport);
+
+    let mut pictures = Vec::new();
+    let drained = cam.drain_pictures(|picture| pictures.push(picture)).await.unwrap();
+
+    assert_eq!(drained, 2);
+    assert_eq!(pictures, [b"first".to_vec(), b"second".to_vec()]);
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test(start_paused = true)]
+async fn unverified_picture_is_read_again() {
+    let mut transport = MockTransport::new();
+    transport
+        .reply([0]) // start_live_view
+        .reply([0]) // clear_camera_pic_buf
+        .reply([0]) // take_picture
+        .reply([3])
+        .reply(framed(true, b"corrupt"))
+        .reply([1]) // not confirmed
+        .reply(framed(true, b"picture"))
+        .reply([0]);
+
+    let mut cam = HaCam::from_transport(transport);
+    cam.start_live_view(LiveViewResolution::Low).await.unwrap();
+    cam.set_picture_transfer_verification(true);
+
+    let jpeg = cam
+        .take_picture_and_get(PictureOrientation::Deg0, None::<fn(_)>, true)
+        .await
+        .unwrap();
+
+    assert_eq!(jpeg, b"picture");
+    assert!(cam.transport().is_exhausted());
+
+    // The picture is read again from the start.
+    let commands = cam.transport().commands();
+    assert_eq!(&commands[6][..3], &[122, 5, 2]);
+    assert_eq!(&commands[6][8..12], &[0, 0, 0, 0]);
+}
+
+#[test]
+fn jpeg_markers() {
+    assert!(looks_like_jpeg(b"\xFF\xD8data\xFF\xD9"));
+    assert!(!looks_like_jpeg(b"\xFF\xD8truncated"));
+    assert!(!looks_like_jpeg(b"\xFF\xD8data\xFF\xD9garbage"));
+    assert!(!looks_like_jpeg(b"\xFF\xD9"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn invalid_jpeg_is_read_again() {
+    let mut transport = MockTransport::new();
+    transport
+        .reply([0]) // start_live_view
+        .reply([0]) // clear_camera_pic_buf
+        .reply([0]) // take_picture
+        .reply([3])
+        .reply(framed(true, b"\xFF\xD8truncated"))
+        .reply(framed(true, b"\xFF\xD8data\xFF\xD9"));
+
+    let mut cam = HaCam::from_transport(transport);
+    cam.start_live_view(LiveViewResolution::Low).await.unwrap();
+    cam.set_jpeg_validation(true);
+
+    let jpeg = cam
+        .take_picture_and_get(PictureOrientation::Deg0, None::<fn(_)>, true)
+        .await
+        .unwrap();
+
+    assert_eq!(jpeg, b"\xFF\xD8data\xFF\xD9");
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test(start_paused = true)]
+async fn invalid_jpeg_after_all_tries() {
+    let mut transport = MockTransport::new();
+    transport.reply([0]).reply([0]).reply([0]).reply([3]);
+    for _ in 0..3 {
+        transport.reply(framed(true, b"garbage"));
+    }
+
+    let mut cam = HaCam::from_transport(transport);
+    cam.start_live_view(LiveViewResolution::Low).await.unwrap();
+    cam.set_jpeg_validation(true);
+
+    assert!(matches!(
+        cam.take_picture_and_get(PictureOrientation::Deg0, None::<fn(_)>, true)
+            .await,
+        Err(CamError::CorruptPicture { tries: 3 })
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn take_picture_at_restores_resolution() {
+    let mut live_view_frame = vec![0; 33];
+    live_view_frame[1] = 1;
+    live_view_frame[28] = 1;
+
+    let mut transport = MockTransport::new();
+    transport
+        .reply([PhotoResolution::Low as u8]) // read_setting
+        .push(Reply::Csw(0)) // write_setting
+        .reply([0]) // start_live_view
+        .reply([0]) // check_live_view_status
+        .reply(live_view_frame)
+        .reply([0]) // stop_live_view
+        .reply([0]) // check_live_view_stop_request_status
+        .reply([0]) // clear_camera_pic_buf
+        .reply([0]) // take_picture
+        .reply([3])
+        .reply(framed(true, b"picture"))
+        .push(Reply::Csw(0)); // write_setting
+
+    let mut cam = HaCam::from_transport(transport);
+
+    let jpeg = cam
+        .take_picture_at(PictureOrientation::Deg0, PhotoResolution::High, true)
+        .await
+        .unwrap();
+
+    assert_eq!(jpeg, b"picture");
+    assert!(cam.transport().is_exhausted());
+
+    // The written setting values
+    let sent = &cam.transport().sent;
+    assert!(sent.contains(&vec![PhotoResolution::High as u8]));
+    assert_eq!(sent.last(), Some(&vec![PhotoResolution::Low as u8]));
+}
+
+#[tokio::test]
+async fn strict_live_view_check() {
+    let mut transport = MockTransport::new();
+    transport.reply([0]).reply([0]);
+
+    let mut cam = HaCam::from_transport(transport);
+    cam.set_strict_live_view(true);
+
+    assert!(matches!(
+        cam.take_picture(PictureOrientation::Deg0).await,
+        Err(CamError::LiveViewNotInitialized)
+    ));
+    assert!(matches!(
+        cam.take_picture_and_get(PictureOrientation::Deg0, None::<fn(_)>, true)
+            .await,
+        Err(CamError::LiveViewNotInitialized)
+    ));
+    assert!(cam.transport().commands().is_empty());
+
+    cam.start_live_view(LiveViewResolution::Low).await.unwrap();
+    cam.take_picture(PictureOrientation::Deg0).await.unwrap();
+    assert!(cam.has_initialized_live_view());
+}
+
+#[tokio::test]
+async fn malformed_partial_picture_length() {
+    let mut oversized = framed(false, b"data");
+    oversized[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut transport = MockTransport::new();
+    transport
+        .reply(oversized)
+        .reply(framed(false, b""))
+        .reply(framed(true, b""));
+
+    let mut cam = HaCam::from_transport(transport);
+
+    assert!(matches!(
+        cam.get_partial_picture_buffer(0).await,
+        Err(CamError::InvalidFormat)
+    ));
+    // An empty part has to be the last one
+    assert!(matches!(
+        cam.get_partial_picture_buffer(0).await,
+        Err(CamError::InvalidFormat)
+    ));
+    assert_eq!(
+        cam.get_partial_picture_buffer(0).await.unwrap(),
+        (vec![], true)
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn picture_reader_copies_all_parts() {
+    use tokio::io::AsyncReadExt as _;
+
+    let mut transport = MockTransport::new();
+    transport
+        .reply(framed(false, b"\xFF\xD8first"))
+        .reply(framed(true, b"second\xFF\xD9"));
+
+    let mut cam = HaCam::from_transport(transport);
+
+    // A small buffer, so the parts are read across multiple calls
+    let mut reader = cam.picture_reader();
+    let mut jpeg = Vec::new();
+    let mut buf = [0; 3];
+
+    loop {
+        let len = reader.read(&mut buf).await.unwrap();
+        if len == 0 {
+            break;
+        }
+
+        jpeg.extend_from_slice(&buf[..len]);
+    }
+
+    assert_eq!(jpeg, b"\xFF\xD8firstsecond\xFF\xD9");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn picture_reader_surfaces_errors() {
+    use tokio::io::AsyncReadExt as _;
+
+    let mut transport = MockTransport::new();
+    transport.reply(framed(false, b"part"));
+
+    let mut cam = HaCam::from_transport(transport);
+
+    // The script runs out after the first part, so the second request times out
+    let mut jpeg = Vec::new();
+    let e = cam
+        .picture_reader()
+        .read_to_end(&mut jpeg)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        e.into_inner().unwrap().downcast::<CamError>().as_deref(),
+        Ok(CamError::Timeout { .. })
+    ));
+    assert_eq!(jpeg, b"part");
+}
@@ -0,0 +1,37 @@
+use hacam_lib_rs::{cam::build_command_header, consts};
+
+#[test]
+fn read_command_header_layout() {
+    let header = build_command_header(
+        &consts::usb::GET_CAMERA_STATUS,
+        consts::DEFAULT_MAX_RECV_SIZE as i32,
+        true,
+        0x0102_0304,
+    )
+    .unwrap();
+
+    #[rustfmt::skip]
+    let expected: [u8; 31] = [
+        b'U', b'S', b'B', b'C',     // magic
+        0x01, 0x02, 0x03, 0x04,     // check integer (big-endian)
+        0x00, 0x00, 0x01, 0x00,     // max receiving size (little-endian)
+        0x80,                       // transfer type (read)
+        0x00,                       // LUN
+        0x10,                       // command length
+        122, 3, 48, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // command
+    ];
+
+    assert_eq!(header, expected);
+}
+
+#[test]
+fn write_command_header_is_padded() {
+    let header = build_command_header(&[122, 3], 48, false, -1).unwrap();
+
+    assert_eq!(header.len(), 31);
+    assert_eq!(&header[4..8], &[0xFF; 4]);
+    assert_eq!(&header[8..12], &48i32.to_le_bytes());
+    assert_eq!(header[12], 0);
+    assert_eq!(&header[15..17], &[122, 3]);
+    assert!(header[17..].iter().all(|b| *b == 0));
+}
@@ -0,0 +1,701 @@
+mod common;
+
+use common::{csw, MockTransport, Reply};
+use hacam_lib_rs::{
+    cam::{CaptureStatus, HaCam, Versions},
+    settings::{CamSettings, FilterValue, LiveViewResolution, PhotoResolution, SettingType},
+    CamError, TransferOp,
+};
+
+fn cam_with(transport: MockTransport) -> HaCam<MockTransport> {
+    HaCam::from_transport(transport)
+}
+
+#[tokio::test]
+async fn capture_status_variants() {
+    let mut transport = MockTransport::new();
+    transport.reply([1]).reply([3]);
+
+    let mut status = vec![0, 7, 0, 2, 0, 0, 0, 0];
+    status.extend_from_slice(b"IMG_0002\0");
+    transport.reply(status);
+
+    let mut cam = cam_with(transport);
+
+    assert!(matches!(cam.check_capture_status().await.unwrap(), CaptureStatus::TryAgain));
+    assert!(matches!(cam.check_capture_status().await.unwrap(), CaptureStatus::Captured));
+
+    match cam.check_capture_status().await.unwrap() {
+        CaptureStatus::ThumbnailAvailable {
+            stored_pic_num,
+            is_exposure_ready,
+            picture_status,
+            picture_string,
+        } => {
+            assert_eq!(stored_pic_num, 2);
+            assert!(is_exposure_ready);
+            assert_eq!(picture_status, 7);
+            assert_eq!(picture_string.as_deref(), Some("IMG_0002"));
+        }
+        other => panic!("unexpected capture status {other:?}"),
+    }
+}
+
+#[test]
+fn capture_status_equality() {
+    let thumbnail = |stored_pic_num| CaptureStatus::ThumbnailAvailable {
+        stored_pic_num,
+        is_exposure_ready: true,
+        picture_status: 0,
+        picture_string: None,
+    };
+
+    assert_eq!(thumbnail(1), thumbnail(1));
+    assert_ne!(thumbnail(1), thumbnail(2));
+    assert_ne!(CaptureStatus::TryAgain, CaptureStatus::Captured);
+
+    assert!(CaptureStatus::Captured.is_terminal());
+    assert!(!CaptureStatus::TryAgain.is_terminal());
+    assert!(!thumbnail(1).is_terminal());
+}
+
+#[tokio::test]
+async fn capture_status_too_short() {
+    let mut transport = MockTransport::new();
+    transport.reply([0, 0, 0]);
+
+    let mut cam = cam_with(transport);
+
+    assert!(matches!(
+        cam.check_capture_status().await,
+        Err(CamError::InvalidLength {
+            expected: 9,
+            received: 3
+        })
+    ));
+}
+
+#[tokio::test]
+async fn camera_info_firmware_version() {
+    let mut info = vec![0; 97];
+    info.extend_from_slice(b"v1.2.3\0\0");
+
+    let mut transport = MockTransport::new();
+    transport.reply(info).reply(vec![0; 97]);
+
+    let mut cam = cam_with(transport);
+
+    assert_eq!(cam.get_camera_info().await.unwrap().as_deref(), Some("v1.2.3"));
+    assert!(matches!(
+        cam.get_camera_info().await,
+        Err(CamError::InvalidLength {
+            expected: 98,
+            received: 97
+        })
+    ));
+}
+
+#[tokio::test]
+async fn settings_round_trip() {
+    let settings = CamSettings::builder()
+        .photo_resolution(PhotoResolution::High)
+        .filter(FilterValue::Tea)
+        .build();
+
+    let mut transport = MockTransport::new();
+    transport.reply(settings.to_bytes());
+
+    let mut cam = cam_with(transport);
+
+    let read = cam.read_all_settings().await.unwrap();
+
+    assert_eq!(read.to_bytes(), settings.to_bytes());
+    assert_eq!(cam.transport().commands().len(), 1);
+}
+
+#[tokio::test]
+async fn data_and_csw_in_one_transfer() {
+    let mut transport = MockTransport::new();
+    transport.push(Reply::DataWithCsw(vec![0, 42], 0));
+
+    let mut cam = cam_with(transport);
+
+    assert_eq!(cam.send_status_query(&[122, 3, 53]).await.unwrap(), [0, 42]);
+    assert_eq!(cam.last_csw().unwrap().status, 0);
+}
+
+#[tokio::test]
+async fn live_view_resolution_byte() {
+    let mut transport = MockTransport::new();
+    transport.reply([0]).reply([0]);
+
+    let mut cam = cam_with(transport);
+
+    cam.start_live_view(LiveViewResolution::High).await.unwrap();
+    cam.start_live_view(LiveViewResolution::Low).await.unwrap();
+
+    let commands = cam.transport().commands();
+    assert_eq!(commands[0][9], 9);
+    assert_eq!(commands[1][9], 10);
+}
+
+#[tokio::test]
+async fn overflowing_read_is_an_error() {
+    let mut transport = MockTransport::new();
+    transport.reply(vec![0; 20]);
+
+    let mut cam = cam_with(transport);
+
+    let res = cam
+        .send_raw_read(&[122, 3, 1], 16, std::time::Duration::from_secs(1))
+        .await;
+
+    assert!(matches!(
+        res,
+        Err(CamError::InvalidLength {
+            expected: 16,
+            received: 20
+        })
+    ));
+    // The rest of the response (including the CSW) has been drained.
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test]
+async fn zero_length_packets_are_skipped() {
+    let mut transport = MockTransport::new();
+    transport
+        .push(Reply::Data(vec![0, 1]))
+        .push(Reply::Data(vec![]))
+        .push(Reply::Data(vec![2]))
+        .push(Reply::Csw(0));
+    for _ in 0..4 {
+        transport.push(Reply::Data(vec![]));
+    }
+
+    let mut cam = cam_with(transport);
+
+    assert_eq!(cam.send_status_query(&[122, 3, 53]).await.unwrap(), [0, 1, 2]);
+    assert!(matches!(
+        cam.send_status_query(&[122, 3, 53]).await,
+        Err(CamError::InvalidFormat)
+    ));
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test(start_paused = true)]
+async fn init_reports_last_status_and_attempts() {
+    let mut transport = MockTransport::new();
+    for _ in 0..3 {
+        transport.push(Reply::Data(vec![1]));
+    }
+
+    let mut cam = cam_with(transport);
+
+    assert!(matches!(
+        cam.initialize_comm().await,
+        Err(CamError::ConnInit {
+            tries: 3,
+            status_code: 1
+        })
+    ));
+}
+
+// The mock delays run on the paused Tokio clock, which the library only follows with Tokio timers.
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn wake_pings_until_the_camera_responds() {
+    let mut transport = MockTransport::new();
+    transport.push(Reply::Data(vec![0]));
+    // The first ping times out
+    transport.push(Reply::Delay(std::time::Duration::from_secs(5)));
+    transport.push(Reply::Data(vec![]));
+    transport.reply([0, 118]);
+
+    let mut cam = cam_with(transport);
+    cam.set_init_attempts(3, std::time::Duration::from_millis(100));
+
+    cam.wake().await.unwrap();
+
+    assert_eq!(cam.transport().commands().len(), 3);
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test(start_paused = true)]
+async fn wake_fails_if_the_camera_stays_silent() {
+    let mut transport = MockTransport::new();
+    transport.push(Reply::Data(vec![0]));
+
+    let mut cam = cam_with(transport);
+    cam.set_init_attempts(2, std::time::Duration::from_millis(100));
+
+    assert!(matches!(
+        cam.wake().await,
+        Err(CamError::Timeout {
+            op: TransferOp::StatusPoll
+        })
+    ));
+    assert_eq!(cam.transport().commands().len(), 3);
+}
+
+#[tokio::test]
+async fn app_connect_status() {
+    let mut transport = MockTransport::new();
+    transport
+        .push(Reply::Data(vec![0]))
+        .push(Reply::Data(vec![2]));
+
+    let mut cam = cam_with(transport);
+
+    cam.app_connect().await.unwrap();
+    assert!(matches!(
+        cam.app_connect().await,
+        Err(CamError::ConnInit {
+            tries: 1,
+            status_code: 2
+        })
+    ));
+
+    let commands = cam.transport().commands();
+    assert_eq!(&commands[0][..3], &[122, 0, 2]);
+    assert_eq!(commands[0][9], 1);
+}
+
+#[test]
+fn debug_output() {
+    let cam = cam_with(MockTransport::new());
+
+    let debug = format!("{cam:?}");
+
+    assert!(debug.starts_with("HaCam { transport: MockTransport {"));
+    assert!(debug.contains("default_tries: 3"));
+}
+
+#[test]
+fn csw_is_parsed_from_the_end() {
+    let mut buf = vec![9, 9];
+    buf.extend_from_slice(&csw(-5, 4));
+
+    let parsed = hacam_lib_rs::cam::Csw::parse(&buf).unwrap();
+
+    assert_eq!((parsed.tag, parsed.residue, parsed.status), (-5, 0, 4));
+    assert!(hacam_lib_rs::cam::Csw::parse(&buf[..buf.len() - 1]).is_none());
+}
+
+/// Builds a live view frame part, with the `is_last` flag at offset 1,
+/// the thermal status at offset 20 and the length at offset 28.
+fn frame_part(is_last: bool, data: &[u8]) -> Vec<u8> {
+    let mut buf = vec![0; 32];
+    buf[1] = is_last as u8;
+    buf[28..32].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+    buf
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn live_view_frame_timeout_spans_all_parts() {
+    let mut transport = MockTransport::new();
+    for _ in 0..3 {
+        transport
+            .push(Reply::Delay(std::time::Duration::from_secs(1)))
+            .reply(frame_part(false, b"part"));
+    }
+
+    let mut cam = cam_with(transport);
+    let start = tokio::time::Instant::now();
+
+    assert!(matches!(
+        cam.get_live_view_frame_with_timeout(std::time::Duration::from_millis(2500))
+            .await,
+        Err(CamError::Timeout {
+            op: TransferOp::LiveViewFrame
+        })
+    ));
+
+    // The part in flight at the deadline is still received completely, including its status.
+    assert_eq!(start.elapsed(), std::time::Duration::from_secs(3));
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test]
+async fn live_view_frame_parts() {
+    let mut transport = MockTransport::new();
+    // A frame sent in a single message.
+    transport.reply(frame_part(true, b"single"));
+    // A frame with a zero-length final message.
+    transport
+        .reply(frame_part(false, b"multi"))
+        .reply(frame_part(true, b""));
+
+    let mut cam = cam_with(transport);
+
+    let (_, frame) = cam.get_live_view_frame().await.unwrap();
+    assert_eq!(&frame.data[..], b"single");
+
+    let (_, frame) = cam.get_live_view_frame().await.unwrap();
+    assert_eq!(&frame.data[..], b"multi");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn tiny_target_fps_waits_for_the_max_interval() {
+    use futures::StreamExt as _;
+    use hacam_lib_rs::consts;
+
+    for target_fps in [1e-20, 1e-19, 1e-6] {
+        let mut transport = MockTransport::new();
+        transport
+            .reply(frame_part(true, b"first"))
+            .reply(frame_part(true, b"second"));
+
+        let mut cam = cam_with(transport);
+
+        let start = tokio::time::Instant::now();
+        let frames = cam
+            .live_view_stream_paced(target_fps)
+            .take(2)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(frames.len(), 2);
+        assert!(frames.iter().all(Result::is_ok));
+        assert_eq!(start.elapsed(), consts::MAX_FRAME_INTERVAL);
+    }
+}
+
+#[tokio::test]
+async fn malformed_live_view_frames() {
+    let mut transport = MockTransport::new();
+    transport.reply(frame_part(true, b"")).reply([0; 20]);
+
+    let mut cam = cam_with(transport);
+
+    assert!(matches!(
+        cam.get_live_view_frame().await,
+        Err(CamError::InvalidLength {
+            expected: 1,
+            received: 0
+        })
+    ));
+    assert!(matches!(
+        cam.get_live_view_frame().await,
+        Err(CamError::InvalidLength {
+            expected: 32,
+            received: 20
+        })
+    ));
+}
+
+#[tokio::test]
+async fn settings_applied_individually() {
+    let settings = CamSettings::builder()
+        .photo_resolution(PhotoResolution::High)
+        .filter(FilterValue::Tea)
+        .build();
+
+    let mut transport = MockTransport::new();
+    for _ in 0..7 {
+        transport.push(Reply::Csw(0));
+    }
+
+    let mut cam = cam_with(transport);
+
+    settings.apply_individually(&mut cam, None).await.unwrap();
+
+    let commands = cam.transport().commands();
+    assert_eq!(commands.len(), 7);
+    assert!(commands.iter().all(|cmd| cmd[0] == 123));
+    assert_eq!(commands[0][2], SettingType::PhotoResolution as u8);
+    assert_eq!(
+        cam.transport().sent.last(),
+        Some(&vec![settings.logo_type as u8])
+    );
+}
+
+#[tokio::test]
+async fn settings_applied_individually_with_mask() {
+    let settings = CamSettings::builder().filter(FilterValue::Tea).build();
+
+    let mut transport = MockTransport::new();
+    transport.push(Reply::Csw(0));
+
+    let mut cam = cam_with(transport);
+
+    settings
+        .apply_individually(&mut cam, Some(&[SettingType::Filter]))
+        .await
+        .unwrap();
+
+    let commands = cam.transport().commands();
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0][2], SettingType::Filter as u8);
+    assert_eq!(
+        cam.transport().sent.last(),
+        Some(&vec![FilterValue::Tea as u8])
+    );
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test]
+async fn raw_setting_read() {
+    let mut transport = MockTransport::new();
+    transport.reply([5, 1, 2, 3]).reply([]);
+
+    let mut cam = cam_with(transport);
+
+    assert_eq!(
+        cam.read_setting_raw(SettingType::Filter).await.unwrap(),
+        [5, 1, 2, 3]
+    );
+    assert_eq!(cam.transport().commands()[0][2], SettingType::Filter as u8);
+    assert!(matches!(
+        cam.read_setting_raw(SettingType::Filter).await,
+        Err(CamError::InvalidLength {
+            expected: 1,
+            received: 0
+        })
+    ));
+}
+
+#[tokio::test]
+async fn empty_responses_are_errors() {
+    let mut transport = MockTransport::new();
+    transport.reply([]).reply([]).reply([0]);
+
+    let mut cam = cam_with(transport);
+
+    assert!(matches!(
+        cam.read_setting(SettingType::Filter).await,
+        Err(CamError::InvalidLength {
+            expected: 1,
+            received: 0
+        })
+    ));
+    assert!(matches!(
+        cam.query_remaining_pic_num().await,
+        Err(CamError::InvalidLength {
+            expected: 1,
+            received: 0
+        })
+    ));
+    assert!(matches!(
+        cam.get_scsi_version().await,
+        Err(CamError::InvalidLength {
+            expected: 2,
+            received: 1
+        })
+    ));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn transfer_observer_sees_every_transfer() {
+    use hacam_lib_rs::transport::TransferDirection;
+
+    let mut transport = MockTransport::new();
+    transport
+        .push(Reply::Delay(std::time::Duration::from_millis(30)))
+        .reply([0, 42]);
+
+    let mut cam = cam_with(transport);
+
+    let transfers = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    cam.set_transfer_observer(Some({
+        let transfers = std::sync::Arc::clone(&transfers);
+        move |direction, bytes, duration| {
+            transfers.lock().unwrap().push((direction, bytes, duration));
+        }
+    }));
+
+    cam.send_status_query(&[122, 3, 53]).await.unwrap();
+
+    let transfers = transfers.lock().unwrap();
+    assert_eq!(transfers.len(), 3);
+    assert_eq!((transfers[0].0, transfers[0].1), (TransferDirection::Out, 31));
+    assert_eq!((transfers[1].0, transfers[1].1), (TransferDirection::In, 2));
+    assert_eq!(transfers[1].2, std::time::Duration::from_millis(30));
+    assert_eq!((transfers[2].0, transfers[2].1), (TransferDirection::In, 13));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn keepalives_are_sent_during_a_future() {
+    use hacam_lib_rs::shared::HaCamShared;
+
+    let mut transport = MockTransport::new();
+    transport.push(Reply::Data(vec![0])).push(Reply::Data(vec![0]));
+
+    let shared = HaCamShared::new(cam_with(transport));
+
+    let res = shared
+        .with_keepalive(async {
+            tokio::time::sleep(std::time::Duration::from_millis(1200)).await;
+            Ok(7)
+        })
+        .await;
+    assert_eq!(res.unwrap(), 7);
+
+    let Ok(cam) = shared.try_into_inner() else {
+        panic!("the camera is still shared");
+    };
+    assert_eq!(cam.transport().commands().len(), 2);
+    assert!(cam.transport().is_exhausted());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn keepalives_are_skipped_while_the_camera_is_locked() {
+    use hacam_lib_rs::shared::HaCamShared;
+
+    let shared = HaCamShared::new(cam_with(MockTransport::new()));
+
+    shared
+        .with_keepalive(async {
+            let _cam = shared.lock().await;
+            tokio::time::sleep(std::time::Duration::from_millis(1200)).await;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    let Ok(cam) = shared.try_into_inner() else {
+        panic!("the camera is still shared");
+    };
+    assert!(cam.transport().commands().is_empty());
+}
+
+#[tokio::test]
+async fn versions_are_fetched_together() {
+    let mut info = vec![0; 97];
+    info.extend_from_slice(b"v1.2.3\0\0");
+
+    let mut transport = MockTransport::new();
+    transport.reply(info).reply(b"\0v0.9\0".to_vec());
+
+    let mut cam = cam_with(transport);
+
+    assert_eq!(
+        cam.get_versions().await.unwrap(),
+        Versions {
+            firmware: Some("v1.2.3".to_owned()),
+            scsi: Some("v0.9".to_owned()),
+        }
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn init_retries_transient_transfer_errors() {
+    use nusb::transfer::TransferError;
+
+    let mut transport = MockTransport::new();
+    transport
+        .push(Reply::Error(CamError::UsbTransfer(TransferError::Stall)))
+        .push(Reply::Error(CamError::UsbTransfer(TransferError::Stall)))
+        .push(Reply::Data(vec![0]));
+
+    let mut cam = cam_with(transport);
+    cam.set_init_attempts(1, std::time::Duration::ZERO);
+
+    cam.initialize_comm().await.unwrap();
+    assert_eq!(cam.transport().commands().len(), 3);
+
+    let mut transport = MockTransport::new();
+    transport.push(Reply::Error(CamError::UsbTransfer(TransferError::Disconnected)));
+
+    let mut cam = cam_with(transport);
+
+    assert!(cam.initialize_comm().await.unwrap_err().is_disconnect());
+    assert_eq!(cam.transport().commands().len(), 1);
+}
+
+#[tokio::test]
+async fn dropped_live_view_handle_stops_before_the_next_command() {
+    let mut transport = MockTransport::new();
+    transport.reply([0]).reply([0]).reply([4]);
+
+    let mut cam = cam_with(transport);
+
+    let handle = cam
+        .start_live_view_handle(LiveViewResolution::Low)
+        .await
+        .unwrap();
+    drop(handle);
+
+    assert!(cam.has_pending_live_view_stop());
+    assert_eq!(cam.query_remaining_pic_num().await.unwrap(), 4);
+    assert!(!cam.has_pending_live_view_stop());
+
+    let prefixes = cam
+        .transport()
+        .commands()
+        .iter()
+        .map(|cmd| cmd[..3].to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(prefixes, [[122, 1, 1], [122, 1, 2], [122, 3, 53]]);
+}
+
+#[tokio::test]
+async fn dropped_live_view_handle_stops_before_a_keepalive() {
+    let mut transport = MockTransport::new();
+    transport.reply([0]).reply([0]).push(Reply::Data(vec![0]));
+
+    let mut cam = cam_with(transport);
+
+    let handle = cam
+        .start_live_view_handle(LiveViewResolution::Low)
+        .await
+        .unwrap();
+    drop(handle);
+
+    cam.send_keepalive().await.unwrap();
+    assert!(!cam.has_pending_live_view_stop());
+
+    let prefixes = cam
+        .transport()
+        .commands()
+        .iter()
+        .map(|cmd| cmd[..3].to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(prefixes, [[122, 1, 1], [122, 1, 2], [122, 3, 255]]);
+}
+
+#[tokio::test]
+async fn stopped_live_view_handle() {
+    let mut transport = MockTransport::new();
+    transport.reply([0]).reply([0]).reply([0]);
+
+    let mut cam = cam_with(transport);
+
+    let handle = cam
+        .start_live_view_handle(LiveViewResolution::Low)
+        .await
+        .unwrap();
+    handle
+        .stop(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+
+    assert!(!cam.has_pending_live_view_stop());
+    assert!(!cam.is_live_view_active());
+    assert!(cam.transport().is_exhausted());
+}
+
+#[tokio::test]
+async fn recording_flag_follows_the_confirmations() {
+    let mut transport = MockTransport::new();
+    transport.reply([0]).reply([3]).reply([0]).reply([0]).reply([0]);
+
+    let mut cam = cam_with(transport);
+
+    cam.start_recording().await.unwrap();
+    assert!(!cam.is_recording());
+    assert!(!cam.check_start_recording_request().await.unwrap());
+    assert!(!cam.is_recording());
+    assert!(cam.check_start_recording_request().await.unwrap());
+    assert!(cam.is_recording());
+
+    cam.stop_recording().await.unwrap();
+    assert!(cam.is_recording());
+    assert!(cam.check_stop_recording_request().await.unwrap());
+    assert!(!cam.is_recording());
+}
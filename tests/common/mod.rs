@@ -0,0 +1,129 @@
+#![allow(dead_code)]
+
+use std::{collections::VecDeque, future::Future, time::Duration};
+
+use hacam_lib_rs::{transport::Transport, CamError, TransferOp};
+
+/// A scripted response of the mock camera.
+#[derive(Debug)]
+pub enum Reply {
+    /// Raw data, split into multiple transfers if it's longer than the requested length.
+    Data(Vec<u8>),
+    /// A CSW with the given status, tagged with the check integer of the last command.
+    Csw(u8),
+    /// Data directly followed by a CSW with the given status, in a single transfer.
+    DataWithCsw(Vec<u8>, u8),
+    /// Delays the next reply.
+    Delay(Duration),
+    /// Fails the transfer with the given error.
+    Error(CamError),
+}
+
+/// Transport returning scripted responses, while recording everything sent to it.
+///
+/// Once the script runs out, every read times out.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    replies: VecDeque<Reply>,
+    /// Every buffer sent to the bulk OUT endpoint.
+    pub sent: Vec<Vec<u8>>,
+    /// Check integer of the last command header.
+    tag: i32,
+    /// Amount of reads which were handed a previously received buffer to reuse.
+    pub reused_bufs: usize,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a raw reply.
+    pub fn push(&mut self, reply: Reply) -> &mut Self {
+        self.replies.push_back(reply);
+        self
+    }
+
+    /// Queues a response to a read command - the data followed by a successful CSW.
+    pub fn reply(&mut self, data: impl Into<Vec<u8>>) -> &mut Self {
+        self.push(Reply::Data(data.into())).push(Reply::Csw(0))
+    }
+
+    /// Returns the command buffers (without the header) of the sent commands.
+    pub fn commands(&self) -> Vec<&[u8]> {
+        self.sent
+            .iter()
+            .filter(|buf| buf.len() == 31 && buf.starts_with(b"USBC"))
+            .map(|buf| &buf[15..])
+            .collect()
+    }
+
+    /// Returns `true` if all of the scripted replies were consumed.
+    pub fn is_exhausted(&self) -> bool {
+        self.replies.is_empty()
+    }
+}
+
+impl Transport for MockTransport {
+    fn bulk_out(&mut self, data: Vec<u8>) -> impl Future<Output = Result<(), CamError>> + Send {
+        if data.len() == 31 && data.starts_with(b"USBC") {
+            self.tag = i32::from_be_bytes(data[4..8].try_into().unwrap());
+        }
+
+        self.sent.push(data);
+
+        std::future::ready(Ok(()))
+    }
+
+    fn bulk_in(
+        &mut self,
+        buf: Vec<u8>,
+        len: usize,
+    ) -> impl Future<Output = Result<Vec<u8>, CamError>> + Send {
+        if buf.capacity() > 0 {
+            self.reused_bufs += 1;
+        }
+
+        let mut delay = Duration::ZERO;
+
+        while let Some(Reply::Delay(d)) = self.replies.front() {
+            delay += *d;
+            self.replies.pop_front();
+        }
+
+        let res = match self.replies.pop_front() {
+            Some(Reply::Data(mut data)) => {
+                if data.len() > len {
+                    let rest = data.split_off(len);
+                    self.replies.push_front(Reply::Data(rest));
+                }
+
+                Ok(data)
+            }
+            Some(Reply::Csw(status)) => Ok(csw(self.tag, status)),
+            Some(Reply::DataWithCsw(mut data, status)) => {
+                data.extend_from_slice(&csw(self.tag, status));
+                Ok(data)
+            }
+            Some(Reply::Error(e)) => Err(e),
+            Some(Reply::Delay(_)) => unreachable!(),
+            None => Err(CamError::Timeout {
+                op: TransferOp::ChunkRead,
+            }),
+        };
+
+        async move {
+            tokio::time::sleep(delay).await;
+            res
+        }
+    }
+}
+
+/// Builds a CSW with the given tag and status.
+pub fn csw(tag: i32, status: u8) -> Vec<u8> {
+    let mut csw = b"USBS".to_vec();
+    csw.extend_from_slice(&tag.to_be_bytes());
+    csw.extend_from_slice(&0u32.to_le_bytes());
+    csw.push(status);
+    csw
+}
@@ -0,0 +1,41 @@
+use hacam_lib_rs::{
+    cam::ThermalStatus,
+    settings::{
+        Bitrate, EvValue, FilterValue, LiveViewResolution, LogoType, PhotoResolution,
+        VideoResolution, WhiteBalance,
+    },
+};
+
+/// Asserts that converting every value into the raw byte and back yields the same value.
+fn assert_round_trip<T>(values: &[T])
+where
+    T: Copy + std::fmt::Debug + Into<i8> + TryFrom<i8>,
+{
+    for &value in values {
+        let raw: i8 = value.into();
+        let back = T::try_from(raw).unwrap_or_else(|_| panic!("{value:?} didn't round-trip"));
+
+        assert_eq!(raw, back.into(), "{value:?}");
+    }
+}
+
+#[test]
+fn thermal_status_round_trip() {
+    use ThermalStatus::*;
+
+    for status in [Ok, OverheatLow, OverheatHigh, Cold] {
+        assert_eq!(ThermalStatus::try_from(i8::from(status)), Result::Ok(status));
+    }
+}
+
+#[test]
+fn setting_enums_round_trip() {
+    assert_round_trip(LiveViewResolution::all());
+    assert_round_trip(PhotoResolution::all());
+    assert_round_trip(VideoResolution::all());
+    assert_round_trip(EvValue::all());
+    assert_round_trip(WhiteBalance::all());
+    assert_round_trip(FilterValue::all());
+    assert_round_trip(LogoType::all());
+    assert_round_trip(Bitrate::all());
+}
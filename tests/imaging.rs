@@ -0,0 +1,75 @@
+#![cfg(feature = "image")]
+
+use hacam_lib_rs::{
+    decode::RgbFrame,
+    imaging::{decode_thumbnail, rotate_equirectangular},
+    settings::PictureOrientation,
+    CamError,
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 16;
+
+/// Creates a JPEG picture with 4 vertical stripes (red, green, blue, white), each 90° wide.
+fn striped_picture() -> Vec<u8> {
+    let stripes = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+
+    let data = (0..HEIGHT)
+        .flat_map(|_| (0..WIDTH).flat_map(|x| stripes[(x / (WIDTH / 4)) as usize]))
+        .collect();
+
+    RgbFrame {
+        width: WIDTH,
+        height: HEIGHT,
+        data,
+    }
+    .encode_jpeg(100)
+    .unwrap()
+}
+
+/// Returns the color in the middle of the stripe with the given index, rounded to 0 or 255.
+fn stripe_color(jpeg: &[u8], stripe: u32) -> [u8; 3] {
+    let image = image::load_from_memory(jpeg).unwrap().into_rgb8();
+
+    let pixel = image.get_pixel(stripe * WIDTH / 4 + WIDTH / 8, HEIGHT / 2);
+
+    pixel.0.map(|c| if c > 127 { 255 } else { 0 })
+}
+
+#[test]
+fn deg0_keeps_picture() {
+    let jpeg = striped_picture();
+
+    assert_eq!(rotate_equirectangular(&jpeg, PictureOrientation::Deg0).unwrap(), jpeg);
+}
+
+#[test]
+fn rotation_shifts_columns() {
+    let jpeg = striped_picture();
+
+    let rotated = rotate_equirectangular(&jpeg, PictureOrientation::Deg90).unwrap();
+    assert_eq!(stripe_color(&rotated, 0), [255, 255, 255]);
+    assert_eq!(stripe_color(&rotated, 1), [255, 0, 0]);
+
+    let rotated = rotate_equirectangular(&jpeg, PictureOrientation::Deg180).unwrap();
+    assert_eq!(stripe_color(&rotated, 0), [0, 0, 255]);
+    assert_eq!(stripe_color(&rotated, 3), [0, 255, 0]);
+
+    let rotated = rotate_equirectangular(&jpeg, PictureOrientation::Deg270).unwrap();
+    assert_eq!(stripe_color(&rotated, 0), [0, 255, 0]);
+    assert_eq!(stripe_color(&rotated, 3), [255, 0, 0]);
+}
+
+#[test]
+fn decode_thumbnail_returns_pixels() {
+    let (width, height, pixels) = decode_thumbnail(&striped_picture()).unwrap();
+
+    assert_eq!((width, height), (WIDTH, HEIGHT));
+    assert_eq!(pixels.len(), (WIDTH * HEIGHT * 3) as usize);
+}
+
+#[test]
+fn decode_thumbnail_rejects_invalid_data() {
+    assert!(matches!(decode_thumbnail(&[]), Err(CamError::InvalidFormat)));
+    assert!(matches!(decode_thumbnail(b"not a jpeg"), Err(CamError::InvalidFormat)));
+}
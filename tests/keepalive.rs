@@ -0,0 +1,40 @@
+mod common;
+
+use common::{csw, MockTransport, Reply};
+use hacam_lib_rs::{cam::HaCam, CamError};
+
+/// Sends a keepalive, to which the camera responds with the given data.
+async fn keepalive_with(response: Vec<u8>) -> Result<(), CamError> {
+    let mut transport = MockTransport::new();
+    transport.push(Reply::Data(response));
+
+    HaCam::from_transport(transport).send_keepalive().await
+}
+
+#[tokio::test]
+async fn bare_status_byte() {
+    assert!(keepalive_with(vec![0]).await.is_ok());
+    assert!(matches!(
+        keepalive_with(vec![3, 0, 0, 0]).await,
+        Err(CamError::Keepalive { status_code: 3 })
+    ));
+}
+
+#[tokio::test]
+async fn csw_only_response() {
+    assert!(matches!(
+        keepalive_with(csw(1234, 1)).await,
+        Err(CamError::Keepalive { status_code: 1 })
+    ));
+}
+
+#[tokio::test]
+async fn truncated_response() {
+    assert!(matches!(
+        keepalive_with(Vec::new()).await,
+        Err(CamError::InvalidLength {
+            expected: 1,
+            received: 0
+        })
+    ));
+}
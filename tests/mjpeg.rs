@@ -0,0 +1,15 @@
+#![cfg(feature = "mjpeg")]
+
+use hacam_lib_rs::mjpeg::{part, BOUNDARY, CONTENT_TYPE};
+
+#[test]
+fn part_framing() {
+    assert!(CONTENT_TYPE.ends_with(&format!("boundary={BOUNDARY}")));
+
+    let part = part(b"\xFF\xD8jpeg\xFF\xD9");
+
+    assert_eq!(
+        part,
+        b"--hacamframe\r\nContent-Type: image/jpeg\r\nContent-Length: 8\r\n\r\n\xFF\xD8jpeg\xFF\xD9\r\n"
+    );
+}
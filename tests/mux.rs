@@ -0,0 +1,92 @@
+#![cfg(feature = "mux")]
+
+use std::{
+    cell::RefCell,
+    io::{Cursor, Seek, SeekFrom, Write},
+    rc::Rc,
+    time::Duration,
+};
+
+use hacam_lib_rs::{cam::LiveViewFrame, mux::Mp4Recorder, settings::VideoResolution};
+
+/// Writer sharing its buffer, so the file can be read after the recorder is dropped.
+#[derive(Clone, Default)]
+struct SharedWriter(Rc<RefCell<Cursor<Vec<u8>>>>);
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for SharedWriter {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.0.borrow_mut().seek(pos)
+    }
+}
+
+const SPS: &[u8] = &[0, 0, 0, 1, 0x67, 0x42, 0x00, 0x0a, 0xf8, 0x41, 0xa2];
+const PPS: &[u8] = &[0, 0, 0, 1, 0x68, 0xce, 0x38, 0x80];
+const IDR: &[u8] = &[0, 0, 0, 1, 0x65, 0x88, 0x84, 0x00, 0x33, 0xff];
+
+fn frame(index: u64, duration: Duration, interval: Option<Duration>) -> LiveViewFrame {
+    let data = if index == 0 {
+        [SPS, PPS, IDR].concat()
+    } else {
+        IDR.to_vec()
+    };
+
+    LiveViewFrame {
+        duration,
+        index,
+        interval,
+        data: data.into(),
+    }
+}
+
+/// Returns the sample durations from the `stts` box of the MP4 file.
+fn sample_durations(mp4: &[u8]) -> Vec<u32> {
+    let u32_at = |i: usize| u32::from_be_bytes(mp4[i..i + 4].try_into().unwrap());
+
+    let stts = mp4.windows(4).position(|w| w == b"stts").expect("no stts box");
+    // Skips the version and flags.
+    let count = u32_at(stts + 8) as usize;
+
+    (0..count)
+        .flat_map(|i| {
+            let entry = stts + 12 + i * 8;
+            std::iter::repeat_n(u32_at(entry + 4), u32_at(entry) as usize)
+        })
+        .collect()
+}
+
+#[test]
+fn sample_durations_follow_the_frame_timing() {
+    let writer = SharedWriter::default();
+    let mut recorder = Mp4Recorder::new(writer.clone(), VideoResolution::High);
+
+    let frames = [
+        frame(0, Duration::from_millis(5), None),
+        frame(1, Duration::ZERO, Some(Duration::from_millis(40))),
+        frame(2, Duration::ZERO, Some(Duration::from_secs(10))),
+        frame(3, Duration::ZERO, Some(Duration::from_micros(100))),
+        frame(4, Duration::ZERO, Some(Duration::ZERO)),
+    ];
+
+    for frame in &frames {
+        recorder.push_frame(frame);
+    }
+
+    assert_eq!(recorder.frame_count(), 5);
+
+    recorder.finish();
+
+    let mp4 = writer.0.borrow().get_ref().clone();
+
+    // 90 kHz ticks, the sub-fps frame isn't clamped and the zero interval still advances the timestamp.
+    assert_eq!(sample_durations(&mp4), [450, 3600, 900_000, 9, 1]);
+}
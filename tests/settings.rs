@@ -0,0 +1,176 @@
+use chrono::{NaiveDate, Timelike as _};
+use hacam_lib_rs::settings::{
+    Bitrate, CamSettings, FilterValue, PhotoResolution, SettingType, SettingsParseError,
+    VideoResolution, WhiteBalance,
+};
+
+fn settings_at(year: i32, ms: u32) -> CamSettings {
+    let date_time = NaiveDate::from_ymd_opt(year, 12, 31)
+        .unwrap()
+        .and_hms_milli_opt(23, 59, 58, ms)
+        .unwrap();
+
+    CamSettings::builder().date_time(date_time).build()
+}
+
+#[test]
+fn date_time_round_trip() {
+    for year in [2000, 2024, 2038, 2099] {
+        let settings = settings_at(year, 789);
+
+        let parsed = CamSettings::from_bytes(&settings.to_bytes()).unwrap();
+
+        assert_eq!(parsed.date_time, settings.date_time, "year {year}");
+    }
+}
+
+#[test]
+fn year_bytes() {
+    // The year is a little-endian `u16`.
+    let cases = [
+        (1999, [0xCF, 0x07]),
+        (2000, [0xD0, 0x07]),
+        (2024, [0xE8, 0x07]),
+        (2100, [0x34, 0x08]),
+    ];
+
+    for (year, expected) in cases {
+        let bytes = settings_at(year, 0).to_bytes();
+
+        assert_eq!(bytes[10..12], expected, "year {year}");
+    }
+}
+
+#[test]
+fn out_of_range_year_saturates() {
+    assert_eq!(&settings_at(-5, 0).to_bytes()[10..12], &[0, 0]);
+    assert_eq!(&settings_at(70000, 0).to_bytes()[10..12], &[0xFF, 0xFF]);
+}
+
+#[test]
+fn no_millis_zeroes_millisecond_field() {
+    let settings = settings_at(2024, 789);
+
+    let bytes = settings.to_bytes();
+    assert_eq!(&bytes[18..20], &789u16.to_le_bytes());
+
+    let bytes = settings.to_bytes_no_millis();
+    assert_eq!(&bytes[18..20], &[0, 0]);
+
+    let parsed = CamSettings::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.date_time, settings.date_time.with_nanosecond(0).unwrap());
+}
+
+#[test]
+fn white_balance_kelvin() {
+    assert_eq!(WhiteBalance::Sunny.approx_kelvin(), 5200);
+    assert_eq!(WhiteBalance::Cloudy.approx_kelvin(), 6000);
+    assert_eq!(WhiteBalance::Tungsten.approx_kelvin(), 3200);
+    assert_eq!(WhiteBalance::Fluorescent.approx_kelvin(), 4000);
+    assert_eq!(WhiteBalance::Auto.approx_kelvin(), 5500);
+}
+
+#[test]
+fn bitrate_validation() {
+    assert_eq!(Bitrate::Unset.as_mbps(), None);
+    assert_eq!(Bitrate::Bitrate2.as_mbps(), Some(16));
+
+    assert!(!Bitrate::Bitrate0.is_valid_for(VideoResolution::High));
+    assert!(Bitrate::Bitrate1.is_valid_for(VideoResolution::High));
+    assert!(Bitrate::Bitrate0.is_valid_for(VideoResolution::Low));
+    assert!(Bitrate::Unset.is_valid_for(VideoResolution::High));
+}
+
+#[test]
+fn filter_list_covers_every_value() {
+    let all = FilterValue::all();
+
+    assert_eq!(all.len(), 9);
+
+    for (i, filter) in all.iter().enumerate() {
+        assert_eq!(*filter as i8, i as i8);
+        assert!(!filter.display_name().is_empty());
+    }
+}
+
+#[test]
+fn selectable_excludes_placeholders() {
+    assert_eq!(Bitrate::all().len(), 4);
+    assert!(
+        !Bitrate::selectable()
+            .iter()
+            .any(|b| matches!(b, Bitrate::Unset))
+    );
+
+    assert_eq!(VideoResolution::all().len(), 3);
+    assert!(
+        !VideoResolution::selectable()
+            .iter()
+            .any(|r| matches!(r, VideoResolution::Unknown))
+    );
+}
+
+#[test]
+fn jpeg_size_estimate_scales_with_resolution() {
+    let high = PhotoResolution::High.estimated_jpeg_bytes();
+    let low = PhotoResolution::Low.estimated_jpeg_bytes();
+
+    assert!(high > low);
+    assert_eq!(low, 3840 * 1920 / 2);
+}
+
+#[test]
+fn unknown_bytes_survive_round_trip() {
+    let mut bytes = settings_at(2024, 0).to_bytes();
+    bytes[0] = 0x5A;
+    bytes[20..32].fill(0xC3);
+    bytes[40..48].fill(0x7E);
+    // Bytes past the 48 known ones are kept too.
+    bytes.extend_from_slice(&[1, 2, 3, 4]);
+
+    let parsed = CamSettings::from_bytes(&bytes).unwrap();
+
+    assert_eq!(parsed.raw(), Some(&bytes[..]));
+    assert_eq!(parsed.to_bytes(), bytes);
+}
+
+#[test]
+fn setting_map_round_trip() {
+    let settings = CamSettings::builder()
+        .photo_resolution(PhotoResolution::Low)
+        .white_balance(WhiteBalance::Cloudy)
+        .filter(FilterValue::all()[3])
+        .build();
+
+    let map = settings.as_setting_map();
+    assert!(map.contains(&(SettingType::WhiteBalance, WhiteBalance::Cloudy as u8)));
+
+    let parsed = CamSettings::from_setting_map(map).unwrap();
+    assert_eq!(parsed.to_bytes()[..10], settings.to_bytes()[..10]);
+    assert_eq!(parsed.to_bytes()[20..], settings.to_bytes()[20..]);
+    assert!(parsed.raw().is_none());
+}
+
+#[test]
+fn setting_map_defaults_and_errors() {
+    let parsed = CamSettings::from_setting_map([
+        (SettingType::PhotoResolution, PhotoResolution::Low as u8),
+        (SettingType::ShutterTime, 5),
+    ])
+    .unwrap();
+
+    assert_eq!(parsed.photo_resolution as u8, PhotoResolution::Low as u8);
+    assert_eq!(
+        parsed.to_bytes()[3..],
+        CamSettings::default().to_bytes()[3..]
+    );
+
+    assert!(matches!(
+        CamSettings::from_setting_map([(SettingType::WhiteBalance, 100)]),
+        Err(SettingsParseError::InvalidValue {
+            offset: 7,
+            value: 100,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,32 @@
+use hacam_lib_rs::cam::{ThermalMonitor, ThermalStatus, ThermalTransition};
+
+#[test]
+fn first_reading_is_accepted() {
+    let mut monitor = ThermalMonitor::new(3);
+
+    assert_eq!(monitor.update(ThermalStatus::OverheatLow), None);
+    assert_eq!(monitor.current(), Some(ThermalStatus::OverheatLow));
+}
+
+#[test]
+fn transition_needs_consecutive_readings() {
+    let mut monitor = ThermalMonitor::new(3);
+    monitor.update(ThermalStatus::OverheatLow);
+
+    // A boundary reading interrupted by the accepted state doesn't count.
+    assert_eq!(monitor.update(ThermalStatus::OverheatHigh), None);
+    assert_eq!(monitor.update(ThermalStatus::OverheatHigh), None);
+    assert_eq!(monitor.update(ThermalStatus::OverheatLow), None);
+    assert_eq!(monitor.update(ThermalStatus::OverheatHigh), None);
+    assert_eq!(monitor.update(ThermalStatus::OverheatHigh), None);
+    assert!(monitor.is_capture_safe());
+
+    assert_eq!(
+        monitor.update(ThermalStatus::OverheatHigh),
+        Some(ThermalTransition {
+            from: ThermalStatus::OverheatLow,
+            to: ThermalStatus::OverheatHigh
+        })
+    );
+    assert!(!monitor.is_capture_safe());
+}